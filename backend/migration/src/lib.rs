@@ -2,6 +2,26 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20260621_000001_create_schema;
 mod m20260621_000002_seed_categories;
+mod m20261018_000003_import_archive;
+mod m20261018_000004_import_mappings;
+mod m20261018_000005_category_rules;
+mod m20261018_000006_bank_category_mappings;
+mod m20261018_000007_jobs;
+mod m20261018_000008_category_suggestions;
+mod m20261018_000009_classifier_models;
+mod m20261018_000010_merchant_categories;
+mod m20261018_000011_category_overrides;
+mod m20261018_000012_category_archive;
+mod m20261018_000013_category_tree;
+mod m20261018_000014_transaction_splits;
+mod m20261018_000015_tags;
+mod m20261018_000016_attachments;
+mod m20261018_000017_transaction_review;
+mod m20261018_000018_transaction_history;
+mod m20261018_000019_transaction_source;
+mod m20261018_000020_transaction_trash;
+mod m20261018_000021_transaction_review_status;
+mod m20261018_000022_suggestion_source;
 
 /// Central migration registry used by the backend and migration CLI.
 pub struct Migrator;
@@ -12,6 +32,26 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20260621_000001_create_schema::Migration),
             Box::new(m20260621_000002_seed_categories::Migration),
+            Box::new(m20261018_000003_import_archive::Migration),
+            Box::new(m20261018_000004_import_mappings::Migration),
+            Box::new(m20261018_000005_category_rules::Migration),
+            Box::new(m20261018_000006_bank_category_mappings::Migration),
+            Box::new(m20261018_000007_jobs::Migration),
+            Box::new(m20261018_000008_category_suggestions::Migration),
+            Box::new(m20261018_000009_classifier_models::Migration),
+            Box::new(m20261018_000010_merchant_categories::Migration),
+            Box::new(m20261018_000011_category_overrides::Migration),
+            Box::new(m20261018_000012_category_archive::Migration),
+            Box::new(m20261018_000013_category_tree::Migration),
+            Box::new(m20261018_000014_transaction_splits::Migration),
+            Box::new(m20261018_000015_tags::Migration),
+            Box::new(m20261018_000016_attachments::Migration),
+            Box::new(m20261018_000017_transaction_review::Migration),
+            Box::new(m20261018_000018_transaction_history::Migration),
+            Box::new(m20261018_000019_transaction_source::Migration),
+            Box::new(m20261018_000020_transaction_trash::Migration),
+            Box::new(m20261018_000021_transaction_review_status::Migration),
+            Box::new(m20261018_000022_suggestion_source::Migration),
         ]
     }
 }
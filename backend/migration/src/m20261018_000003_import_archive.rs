@@ -0,0 +1,101 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ImportLogs::Table)
+                    .add_column(ColumnDef::new(ImportLogs::ContentSha256).text())
+                    .add_column(ColumnDef::new(ImportLogs::ArchivePath).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-import_logs-content_sha256-unique")
+                    .table(ImportLogs::Table)
+                    .col(ImportLogs::ContentSha256)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(ColumnDef::new(Transactions::ImportLogId).integer())
+                    .add_column(ColumnDef::new(Transactions::ImportRow).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-transactions-import_log_id")
+                            .from_tbl(Transactions::Table)
+                            .from_col(Transactions::ImportLogId)
+                            .to_tbl(ImportLogs::Table)
+                            .to_col(ImportLogs::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-transactions-import_log_id")
+                    .table(Transactions::Table)
+                    .col(Transactions::ImportLogId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_foreign_key(Alias::new("fk-transactions-import_log_id"))
+                    .drop_column(Transactions::ImportLogId)
+                    .drop_column(Transactions::ImportRow)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ImportLogs::Table)
+                    .drop_column(ImportLogs::ContentSha256)
+                    .drop_column(ImportLogs::ArchivePath)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ImportLogs {
+    Table,
+    Id,
+    ContentSha256,
+    ArchivePath,
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    ImportLogId,
+    ImportRow,
+}
@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ImportMappings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ImportMappings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ImportMappings::Source).text().not_null())
+                    .col(ColumnDef::new(ImportMappings::Kind).text().not_null())
+                    .col(
+                        ColumnDef::new(ImportMappings::SourceValue)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ImportMappings::AccountId).integer())
+                    .col(ColumnDef::new(ImportMappings::CategoryId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-import_mappings-account_id")
+                            .from(ImportMappings::Table, ImportMappings::AccountId)
+                            .to(Accounts::Table, Accounts::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-import_mappings-category_id")
+                            .from(ImportMappings::Table, ImportMappings::CategoryId)
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-import_mappings-source-kind-value-unique")
+                    .table(ImportMappings::Table)
+                    .col(ImportMappings::Source)
+                    .col(ImportMappings::Kind)
+                    .col(ImportMappings::SourceValue)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ImportMappings::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ImportMappings {
+    Table,
+    Id,
+    Source,
+    Kind,
+    SourceValue,
+    AccountId,
+    CategoryId,
+}
+
+#[derive(DeriveIden)]
+enum Accounts {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+}
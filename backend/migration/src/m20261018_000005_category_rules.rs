@@ -0,0 +1,159 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CategoryRules::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CategoryRules::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CategoryRules::Name).text().not_null())
+                    .col(
+                        ColumnDef::new(CategoryRules::Priority)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(CategoryRules::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(ColumnDef::new(CategoryRules::Counterparty).text())
+                    .col(ColumnDef::new(CategoryRules::DescriptionRegex).text())
+                    .col(ColumnDef::new(CategoryRules::AmountMin).decimal_len(12, 2))
+                    .col(ColumnDef::new(CategoryRules::AmountMax).decimal_len(12, 2))
+                    .col(ColumnDef::new(CategoryRules::AccountId).integer())
+                    .col(ColumnDef::new(CategoryRules::BankType).text())
+                    .col(ColumnDef::new(CategoryRules::BankCategory).text())
+                    .col(
+                        ColumnDef::new(CategoryRules::CategoryId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CategoryRules::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_rules-account_id")
+                            .from(CategoryRules::Table, CategoryRules::AccountId)
+                            .to(Accounts::Table, Accounts::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_rules-category_id")
+                            .from(CategoryRules::Table, CategoryRules::CategoryId)
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(ColumnDef::new(Transactions::CategoryRuleId).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-transactions-category_rule_id")
+                            .from_tbl(Transactions::Table)
+                            .from_col(Transactions::CategoryRuleId)
+                            .to_tbl(CategoryRules::Table)
+                            .to_col(CategoryRules::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-transactions-category_rule_id")
+                    .table(Transactions::Table)
+                    .col(Transactions::CategoryRuleId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_foreign_key(Alias::new("fk-transactions-category_rule_id"))
+                    .drop_column(Transactions::CategoryRuleId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(CategoryRules::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum CategoryRules {
+    Table,
+    Id,
+    Name,
+    Priority,
+    Enabled,
+    Counterparty,
+    DescriptionRegex,
+    AmountMin,
+    AmountMax,
+    AccountId,
+    BankType,
+    BankCategory,
+    CategoryId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    CategoryRuleId,
+}
+
+#[derive(DeriveIden)]
+enum Accounts {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+}
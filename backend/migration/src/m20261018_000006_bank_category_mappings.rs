@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BankCategoryMappings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BankCategoryMappings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(BankCategoryMappings::Bank).text().not_null())
+                    .col(
+                        ColumnDef::new(BankCategoryMappings::BankCategory)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BankCategoryMappings::CategoryId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-bank_category_mappings-category_id")
+                            .from(
+                                BankCategoryMappings::Table,
+                                BankCategoryMappings::CategoryId,
+                            )
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-bank_category_mappings-bank-category-unique")
+                    .table(BankCategoryMappings::Table)
+                    .col(BankCategoryMappings::Bank)
+                    .col(BankCategoryMappings::BankCategory)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(BankCategoryMappings::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum BankCategoryMappings {
+    Table,
+    Id,
+    Bank,
+    BankCategory,
+    CategoryId,
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+}
@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Jobs::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Jobs::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Jobs::Kind).text().not_null())
+                    .col(
+                        ColumnDef::new(Jobs::Status)
+                            .text()
+                            .not_null()
+                            .default("queued"),
+                    )
+                    .col(ColumnDef::new(Jobs::Total).integer().not_null().default(0))
+                    .col(
+                        ColumnDef::new(Jobs::Processed)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Jobs::Succeeded)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Jobs::Failed).integer().not_null().default(0))
+                    .col(ColumnDef::new(Jobs::Error).text())
+                    .col(
+                        ColumnDef::new(Jobs::CancelRequested)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(Jobs::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Jobs::StartedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Jobs::FinishedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-jobs-kind-status")
+                    .table(Jobs::Table)
+                    .col(Jobs::Kind)
+                    .col(Jobs::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Jobs::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Jobs {
+    Table,
+    Id,
+    Kind,
+    Status,
+    Total,
+    Processed,
+    Succeeded,
+    Failed,
+    Error,
+    CancelRequested,
+    CreatedAt,
+    StartedAt,
+    FinishedAt,
+}
@@ -0,0 +1,147 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CategorySuggestions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CategorySuggestions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CategorySuggestions::TransactionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CategorySuggestions::CategoryId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CategorySuggestions::Confidence)
+                            .float()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CategorySuggestions::Status)
+                            .text()
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(ColumnDef::new(CategorySuggestions::JobId).integer())
+                    .col(
+                        ColumnDef::new(CategorySuggestions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(CategorySuggestions::ReviewedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_suggestions-transaction_id")
+                            .from(
+                                CategorySuggestions::Table,
+                                CategorySuggestions::TransactionId,
+                            )
+                            .to(Transactions::Table, Transactions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_suggestions-category_id")
+                            .from(CategorySuggestions::Table, CategorySuggestions::CategoryId)
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_suggestions-job_id")
+                            .from(CategorySuggestions::Table, CategorySuggestions::JobId)
+                            .to(Jobs::Table, Jobs::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One suggestion per transaction: a rejected one keeps the row out of
+        // later categorize jobs
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-category_suggestions-transaction_id-unique")
+                    .table(CategorySuggestions::Table)
+                    .col(CategorySuggestions::TransactionId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-category_suggestions-status")
+                    .table(CategorySuggestions::Table)
+                    .col(CategorySuggestions::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(CategorySuggestions::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum CategorySuggestions {
+    Table,
+    Id,
+    TransactionId,
+    CategoryId,
+    Confidence,
+    Status,
+    JobId,
+    CreatedAt,
+    ReviewedAt,
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Jobs {
+    Table,
+    Id,
+}
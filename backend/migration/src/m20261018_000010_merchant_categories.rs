@@ -0,0 +1,127 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MerchantCategories::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MerchantCategories::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MerchantCategories::Fingerprint)
+                            .text()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MerchantCategories::CategoryId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MerchantCategories::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-merchant_categories-category_id")
+                            .from(MerchantCategories::Table, MerchantCategories::CategoryId)
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Jobs::Table)
+                    .add_column(ColumnDef::new(Jobs::Cached).integer().not_null().default(0))
+                    .add_column(
+                        ColumnDef::new(Jobs::PromptTokens)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(Jobs::CompletionTokens)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(Jobs::EstimatedCost)
+                            .decimal_len(12, 6)
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Jobs::Table)
+                    .drop_column(Jobs::Cached)
+                    .drop_column(Jobs::PromptTokens)
+                    .drop_column(Jobs::CompletionTokens)
+                    .drop_column(Jobs::EstimatedCost)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(MerchantCategories::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MerchantCategories {
+    Table,
+    Id,
+    Fingerprint,
+    CategoryId,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Jobs {
+    Table,
+    Cached,
+    PromptTokens,
+    CompletionTokens,
+    EstimatedCost,
+}
@@ -0,0 +1,115 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CategoryOverrides::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CategoryOverrides::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CategoryOverrides::TransactionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CategoryOverrides::PreviousCategoryId).integer())
+                    .col(
+                        ColumnDef::new(CategoryOverrides::PreviousSource)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CategoryOverrides::CategoryId).integer())
+                    .col(
+                        ColumnDef::new(CategoryOverrides::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_overrides-transaction_id")
+                            .from(CategoryOverrides::Table, CategoryOverrides::TransactionId)
+                            .to(Transactions::Table, Transactions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_overrides-previous_category_id")
+                            .from(
+                                CategoryOverrides::Table,
+                                CategoryOverrides::PreviousCategoryId,
+                            )
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_overrides-category_id")
+                            .from(CategoryOverrides::Table, CategoryOverrides::CategoryId)
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-category_overrides-transaction_id")
+                    .table(CategoryOverrides::Table)
+                    .col(CategoryOverrides::TransactionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(CategoryOverrides::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum CategoryOverrides {
+    Table,
+    Id,
+    TransactionId,
+    PreviousCategoryId,
+    PreviousSource,
+    CategoryId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+}
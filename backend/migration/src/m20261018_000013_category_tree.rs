@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Categories::Table)
+                    .add_column(ColumnDef::new(Categories::ParentId).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-categories-parent_id")
+                            .from_tbl(Categories::Table)
+                            .from_col(Categories::ParentId)
+                            .to_tbl(Categories::Table)
+                            .to_col(Categories::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-categories-parent_id")
+                    .table(Categories::Table)
+                    .col(Categories::ParentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Nest the seeded subcategories under their obvious parents
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE categories c SET parent_id = p.id
+                 FROM categories p
+                 WHERE (c.name, p.name) IN (('Fuel', 'Transport'), ('Pubs & Bars', 'Restaurants & Cafes'))",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Categories::Table)
+                    .drop_foreign_key(Alias::new("fk-categories-parent_id"))
+                    .drop_column(Categories::ParentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+    ParentId,
+}
@@ -0,0 +1,98 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TransactionSplits::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TransactionSplits::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TransactionSplits::TransactionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TransactionSplits::CategoryId).integer())
+                    .col(
+                        ColumnDef::new(TransactionSplits::Amount)
+                            .decimal_len(12, 2)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TransactionSplits::Note).text())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-transaction_splits-transaction_id")
+                            .from(TransactionSplits::Table, TransactionSplits::TransactionId)
+                            .to(Transactions::Table, Transactions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-transaction_splits-category_id")
+                            .from(TransactionSplits::Table, TransactionSplits::CategoryId)
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-transaction_splits-transaction_id")
+                    .table(TransactionSplits::Table)
+                    .col(TransactionSplits::TransactionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(TransactionSplits::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum TransactionSplits {
+    Table,
+    Id,
+    TransactionId,
+    CategoryId,
+    Amount,
+    Note,
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TransactionHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TransactionHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TransactionHistory::TransactionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TransactionHistory::Field).text().not_null())
+                    .col(ColumnDef::new(TransactionHistory::OldValue).json_binary())
+                    .col(ColumnDef::new(TransactionHistory::NewValue).json_binary())
+                    .col(
+                        ColumnDef::new(TransactionHistory::ChangedBy)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TransactionHistory::Reason).text().not_null())
+                    .col(
+                        ColumnDef::new(TransactionHistory::ChangedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-transaction_history-transaction_id")
+                            .from(TransactionHistory::Table, TransactionHistory::TransactionId)
+                            .to(Transactions::Table, Transactions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-transaction_history-transaction_id-changed_at")
+                    .table(TransactionHistory::Table)
+                    .col(TransactionHistory::TransactionId)
+                    .col(TransactionHistory::ChangedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(TransactionHistory::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum TransactionHistory {
+    Table,
+    Id,
+    TransactionId,
+    Field,
+    OldValue,
+    NewValue,
+    ChangedBy,
+    Reason,
+    ChangedAt,
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    Id,
+}
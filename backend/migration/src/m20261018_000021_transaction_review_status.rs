@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(
+                        ColumnDef::new(Transactions::ReviewStatus)
+                            .text()
+                            .not_null()
+                            .default("new"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Rows already marked reviewed through bulk edit keep that status
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE transactions SET review_status = 'reviewed' WHERE reviewed_at IS NOT NULL",
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-transactions-review_status-transaction_date")
+                    .table(Transactions::Table)
+                    .col(Transactions::ReviewStatus)
+                    .col(Transactions::TransactionDate)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-transactions-review_status-transaction_date")
+                    .table(Transactions::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_column(Transactions::ReviewStatus)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Transactions {
+    Table,
+    ReviewStatus,
+    TransactionDate,
+}
@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Suggestions made so far all came from the LLM categorizer
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CategorySuggestions::Table)
+                    .add_column(
+                        ColumnDef::new(CategorySuggestions::Source)
+                            .text()
+                            .not_null()
+                            .default("ai"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CategorySuggestions::Table)
+                    .drop_column(CategorySuggestions::Source)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum CategorySuggestions {
+    Table,
+    Source,
+}
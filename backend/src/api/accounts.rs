@@ -1,12 +1,13 @@
 use axum::extract::State;
 use axum::Json;
+use sea_orm::sea_query::Expr;
 use sea_orm::{
-    ColumnTrait, DatabaseConnection, EntityTrait, FromQueryResult, JoinType, QueryOrder,
-    QuerySelect, RelationTrait,
+    DatabaseConnection, EntityTrait, FromQueryResult, JoinType, QueryOrder, QuerySelect,
+    RelationTrait,
 };
 use serde::Serialize;
 
-use crate::entities::{accounts, transactions};
+use crate::entities::accounts;
 use crate::error::AppError;
 
 #[derive(Debug, Serialize, FromQueryResult)]
@@ -15,6 +16,8 @@ pub struct AccountWithCount {
     pub name: String,
     pub currency: String,
     pub transaction_count: i64,
+    /// Transactions still in the review inbox.
+    pub unreviewed_count: i64,
 }
 
 pub async fn list(
@@ -25,7 +28,16 @@ pub async fn list(
         .column(accounts::Column::Id)
         .column(accounts::Column::Name)
         .column(accounts::Column::Currency)
-        .column_as(transactions::Column::Id.count(), "transaction_count")
+        .column_as(
+            Expr::cust("COUNT(transactions.id) FILTER (WHERE transactions.deleted_at IS NULL)"),
+            "transaction_count",
+        )
+        .column_as(
+            Expr::cust(
+                "COUNT(transactions.id) FILTER (WHERE transactions.deleted_at IS NULL AND transactions.review_status = 'new')",
+            ),
+            "unreviewed_count",
+        )
         .join(JoinType::LeftJoin, accounts::Relation::Transactions.def())
         .group_by(accounts::Column::Id)
         .group_by(accounts::Column::Name)
@@ -0,0 +1,38 @@
+use axum::extract::State;
+use axum::{Extension, Json};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+
+use crate::auth::CurrentUser;
+use crate::config::Config;
+use crate::error::AppError;
+use crate::services;
+use crate::services::history::Actor;
+
+#[derive(Serialize)]
+pub struct ReparseResponse {
+    pub files: i32,
+    pub failed_files: i32,
+    pub updated: i32,
+    pub unchanged: i32,
+    pub splits_cleared: i32,
+}
+
+pub async fn reparse(
+    State(db): State<DatabaseConnection>,
+    State(config): State<Config>,
+    Extension(user): Extension<CurrentUser>,
+) -> Result<Json<ReparseResponse>, AppError> {
+    let actor = Actor::new(&user.0, "reparse");
+    let result = services::import::reparse_archived(&db, &config.archive_dir, &actor)
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(ReparseResponse {
+        files: result.files,
+        failed_files: result.failed_files,
+        updated: result.updated,
+        unchanged: result.unchanged,
+        splits_cleared: result.splits_cleared,
+    }))
+}
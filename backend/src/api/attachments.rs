@@ -0,0 +1,148 @@
+use axum::extract::{Multipart, Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::Json;
+use sea_orm::{DatabaseConnection, EntityTrait};
+
+use crate::entities::{attachments, transactions};
+use crate::error::AppError;
+use crate::services;
+use crate::services::attachments::Store;
+
+pub async fn list(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<attachments::Model>>, AppError> {
+    find_transaction(&db, id).await?;
+
+    let attachments = services::attachments::for_transaction(&db, id)
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(attachments))
+}
+
+/// Attach receipts or invoices (multipart, one file per field). Every file
+/// is checked before any is stored.
+pub async fn upload(
+    State(db): State<DatabaseConnection>,
+    State(store): State<Store>,
+    Path(id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<Vec<attachments::Model>>), AppError> {
+    find_transaction(&db, id).await?;
+
+    let mut files = Vec::new();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}")))?
+    {
+        let filename = field.file_name().unwrap_or("attachment").to_string();
+        let bytes = field
+            .bytes()
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}")))?;
+
+        let content_type = services::attachments::validate(&bytes, store.max_bytes)
+            .map_err(|e| AppError::BadRequest(format!("{filename}: {e}")))?;
+        files.push((filename, content_type, bytes.to_vec()));
+    }
+
+    if files.is_empty() {
+        return Err(AppError::BadRequest("No file provided".into()));
+    }
+
+    let mut saved = Vec::with_capacity(files.len());
+    for (filename, content_type, bytes) in files {
+        let attachment =
+            services::attachments::save(&db, &store, id, &filename, content_type, bytes)
+                .await
+                .map_err(AppError::Internal)?;
+        saved.push(attachment);
+    }
+
+    Ok((StatusCode::CREATED, Json(saved)))
+}
+
+/// Remove all of a transaction's attachments.
+pub async fn delete_all(
+    State(db): State<DatabaseConnection>,
+    State(store): State<Store>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    find_transaction(&db, id).await?;
+
+    services::attachments::remove_all(&db, &store, id)
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn download(
+    State(db): State<DatabaseConnection>,
+    State(store): State<Store>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    let attachment = find(&db, id).await?;
+    let bytes = services::attachments::load(&store, &attachment)
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type.clone()),
+            (
+                header::CONTENT_DISPOSITION,
+                content_disposition(&attachment.filename),
+            ),
+        ],
+        bytes,
+    ))
+}
+
+pub async fn delete(
+    State(db): State<DatabaseConnection>,
+    State(store): State<Store>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    let attachment = find(&db, id).await?;
+
+    services::attachments::remove(&db, &store, &attachment)
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find(db: &DatabaseConnection, id: i32) -> Result<attachments::Model, AppError> {
+    attachments::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Attachment {id} not found")))
+}
+
+async fn find_transaction(db: &DatabaseConnection, id: i32) -> Result<(), AppError> {
+    transactions::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {id} not found")))?;
+    Ok(())
+}
+
+/// `inline` so browsers show receipts; the name is reduced to ASCII so it is
+/// always a valid header value.
+fn content_disposition(filename: &str) -> String {
+    let ascii: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("inline; filename=\"{ascii}\"")
+}
@@ -0,0 +1,155 @@
+use axum::extract::{Path, State};
+use axum::{Extension, Json};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::CurrentUser;
+use crate::entities::{bank_category_mappings, categories};
+use crate::error::AppError;
+use crate::services;
+use crate::services::history::Actor;
+
+#[derive(Deserialize)]
+pub struct MappingBody {
+    pub bank: String,
+    pub bank_category: String,
+    pub category_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct MappingResponse {
+    pub mapping: bank_category_mappings::Model,
+    pub transactions_updated: u64,
+}
+
+#[derive(Serialize)]
+pub struct DeleteResponse {
+    pub transactions_updated: u64,
+}
+
+#[derive(Serialize)]
+pub struct UnmappedRow {
+    pub bank: String,
+    pub bank_category: String,
+    pub count: i64,
+}
+
+pub async fn list_mappings(
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<bank_category_mappings::Model>>, AppError> {
+    let mappings = bank_category_mappings::Entity::find()
+        .order_by_asc(bank_category_mappings::Column::Bank)
+        .order_by_asc(bank_category_mappings::Column::BankCategory)
+        .all(&db)
+        .await?;
+
+    Ok(Json(mappings))
+}
+
+/// Create or replace the mapping for a bank category and apply it to the rows
+/// already imported with it.
+pub async fn upsert_mapping(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Json(body): Json<MappingBody>,
+) -> Result<Json<MappingResponse>, AppError> {
+    let bank = body.bank.trim();
+    if bank.is_empty() || body.bank_category.is_empty() {
+        return Err(AppError::BadRequest(
+            "bank and bank_category are required".into(),
+        ));
+    }
+
+    categories::Entity::find_by_id(body.category_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("Category {} not found", body.category_id)))?;
+
+    bank_category_mappings::Entity::insert(bank_category_mappings::ActiveModel {
+        bank: Set(bank.to_string()),
+        bank_category: Set(body.bank_category.clone()),
+        category_id: Set(body.category_id),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::columns([
+            bank_category_mappings::Column::Bank,
+            bank_category_mappings::Column::BankCategory,
+        ])
+        .update_column(bank_category_mappings::Column::CategoryId)
+        .to_owned(),
+    )
+    .exec(&db)
+    .await?;
+
+    let mapping = bank_category_mappings::Entity::find()
+        .filter(bank_category_mappings::Column::Bank.eq(bank))
+        .filter(bank_category_mappings::Column::BankCategory.eq(body.bank_category.as_str()))
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::Internal("Mapping vanished after upsert".into()))?;
+
+    let transactions_updated = services::bank_categories::reapply(
+        &db,
+        &mapping.bank,
+        &mapping.bank_category,
+        Some(mapping.category_id),
+        &Actor::new(&user.0, "bank_mapping"),
+    )
+    .await
+    .map_err(AppError::Internal)?;
+
+    Ok(Json(MappingResponse {
+        mapping,
+        transactions_updated,
+    }))
+}
+
+/// Remove a mapping; rows that got their category from it become
+/// uncategorised again.
+pub async fn delete_mapping(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+) -> Result<Json<DeleteResponse>, AppError> {
+    let mapping = bank_category_mappings::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Mapping {id} not found")))?;
+
+    bank_category_mappings::Entity::delete_by_id(id)
+        .exec(&db)
+        .await?;
+
+    let transactions_updated = services::bank_categories::reapply(
+        &db,
+        &mapping.bank,
+        &mapping.bank_category,
+        None,
+        &Actor::new(&user.0, "bank_mapping"),
+    )
+    .await
+    .map_err(AppError::Internal)?;
+
+    Ok(Json(DeleteResponse {
+        transactions_updated,
+    }))
+}
+
+pub async fn unmapped(
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<UnmappedRow>>, AppError> {
+    let rows = services::bank_categories::unmapped(&db)
+        .await
+        .map_err(AppError::Internal)?
+        .into_iter()
+        .map(|r| UnmappedRow {
+            bank: r.bank,
+            bank_category: r.bank_category,
+            count: r.count,
+        })
+        .collect();
+
+    Ok(Json(rows))
+}
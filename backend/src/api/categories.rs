@@ -1,16 +1,297 @@
-use axum::extract::State;
-use axum::Json;
-use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
+use crate::auth::CurrentUser;
 use crate::entities::categories;
 use crate::error::AppError;
 use crate::models::Category;
+use crate::services;
+use crate::services::categories::{MergeResult, Tree};
+use crate::services::history::Actor;
 
-pub async fn list(State(db): State<DatabaseConnection>) -> Result<Json<Vec<Category>>, AppError> {
-    let categories = categories::Entity::find()
+#[derive(Deserialize)]
+pub struct ListParams {
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Deserialize)]
+pub struct CreateBody {
+    pub name: String,
+    pub name_pl: Option<String>,
+    pub parent_id: Option<i32>,
+}
+
+/// Fields left out are unchanged; an empty `name_pl` clears it and
+/// `"parent_id": null` moves the category to the top level.
+#[derive(Deserialize)]
+pub struct UpdateBody {
+    pub name: Option<String>,
+    pub name_pl: Option<String>,
+    #[serde(default, deserialize_with = "super::present")]
+    pub parent_id: Option<Option<i32>>,
+    pub archived: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct MergeBody {
+    pub into: i32,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteParams {
+    /// Move the category's transactions, rules and mappings here first.
+    pub reassign_to: Option<i32>,
+}
+
+pub async fn list(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<Category>>, AppError> {
+    let mut query = categories::Entity::find();
+    if !params.include_archived {
+        query = query.filter(categories::Column::ArchivedAt.is_null());
+    }
+
+    let categories = query
         .order_by_asc(categories::Column::Name)
         .all(&db)
         .await?;
 
     Ok(Json(categories))
 }
+
+#[derive(Serialize)]
+pub struct CategoryNode {
+    #[serde(flatten)]
+    pub category: Category,
+    pub children: Vec<CategoryNode>,
+}
+
+/// Categories nested under their parents, each level sorted by name.
+pub async fn tree(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<CategoryNode>>, AppError> {
+    let mut query = categories::Entity::find();
+    if !params.include_archived {
+        query = query.filter(categories::Column::ArchivedAt.is_null());
+    }
+    let all = query
+        .order_by_asc(categories::Column::Name)
+        .all(&db)
+        .await?;
+
+    let tree = Tree::new(&all);
+    let by_id: HashMap<i32, &Category> = all.iter().map(|c| (c.id, c)).collect();
+
+    fn node(id: i32, tree: &Tree, by_id: &HashMap<i32, &Category>) -> CategoryNode {
+        CategoryNode {
+            category: by_id[&id].clone(),
+            children: tree
+                .children(id)
+                .iter()
+                .map(|&child| node(child, tree, by_id))
+                .collect(),
+        }
+    }
+
+    let roots = all
+        .iter()
+        .filter(|c| tree.depth(c.id) == 1)
+        .map(|c| node(c.id, &tree, &by_id))
+        .collect();
+
+    Ok(Json(roots))
+}
+
+pub async fn create(
+    State(db): State<DatabaseConnection>,
+    Json(body): Json<CreateBody>,
+) -> Result<(StatusCode, Json<Category>), AppError> {
+    let name = valid_name(&body.name)?;
+    ensure_unique(&db, &name, None).await?;
+    if let Some(parent_id) = body.parent_id {
+        valid_parent(&db, None, parent_id).await?;
+    }
+
+    let category = categories::ActiveModel {
+        name: Set(name),
+        name_pl: Set(body.name_pl.filter(|n| !n.trim().is_empty())),
+        parent_id: Set(body.parent_id),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(category)))
+}
+
+/// Rename, move and/or archive a category. Archived categories stay on their
+/// transactions but are hidden from the list and from AI categorization.
+pub async fn update(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Json(body): Json<UpdateBody>,
+) -> Result<Json<Category>, AppError> {
+    let existing = find(&db, id).await?;
+    let mut active: categories::ActiveModel = existing.into();
+
+    if let Some(ref name) = body.name {
+        let name = valid_name(name)?;
+        ensure_unique(&db, &name, Some(id)).await?;
+        active.name = Set(name);
+    }
+    if let Some(name_pl) = body.name_pl {
+        active.name_pl = Set(Some(name_pl).filter(|n| !n.trim().is_empty()));
+    }
+    if let Some(parent_id) = body.parent_id {
+        if let Some(parent_id) = parent_id {
+            valid_parent(&db, Some(id), parent_id).await?;
+        }
+        active.parent_id = Set(parent_id);
+    }
+    if let Some(archived) = body.archived {
+        active.archived_at = Set(archived.then(Utc::now));
+    }
+
+    let category = active.update(&db).await?;
+    Ok(Json(category))
+}
+
+/// Move everything in category `id` to `into` and delete `id`.
+pub async fn merge(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+    Json(body): Json<MergeBody>,
+) -> Result<Json<MergeResult>, AppError> {
+    let (source, target) = merge_pair(&db, id, body.into).await?;
+
+    let result = services::categories::merge(&db, &source, &target, &Actor::new(&user.0, "merge"))
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(result))
+}
+
+/// Delete a category nothing refers to. With `?reassign_to=` its
+/// transactions, rules and mappings are merged into that category first.
+pub async fn delete(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+    Query(params): Query<DeleteParams>,
+) -> Result<StatusCode, AppError> {
+    if let Some(target) = params.reassign_to {
+        let (source, target) = merge_pair(&db, id, target).await?;
+        services::categories::merge(&db, &source, &target, &Actor::new(&user.0, "merge"))
+            .await
+            .map_err(AppError::Internal)?;
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
+    let category = find(&db, id).await?;
+    let references = services::categories::references(&db, id)
+        .await
+        .map_err(AppError::Internal)?;
+    if !references.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Category '{}' is still used by {} subcategories, {} transactions, {} splits, {} rules, {} bank category mappings and {} import mappings; pass reassign_to or archive it instead",
+            category.name,
+            references.children,
+            references.transactions,
+            references.splits,
+            references.rules,
+            references.bank_mappings,
+            references.import_mappings
+        )));
+    }
+
+    categories::Entity::delete_by_id(id).exec(&db).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find(db: &DatabaseConnection, id: i32) -> Result<categories::Model, AppError> {
+    categories::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Category {id} not found")))
+}
+
+async fn merge_pair(
+    db: &DatabaseConnection,
+    source: i32,
+    target: i32,
+) -> Result<(categories::Model, categories::Model), AppError> {
+    if source == target {
+        return Err(AppError::BadRequest(
+            "Cannot merge a category into itself".into(),
+        ));
+    }
+
+    let source = find(db, source).await?;
+    let target = categories::Entity::find_by_id(target)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("Category {target} not found")))?;
+
+    Ok((source, target))
+}
+
+/// The parent must exist and must not be the category itself or one of its
+/// descendants.
+async fn valid_parent(
+    db: &DatabaseConnection,
+    id: Option<i32>,
+    parent_id: i32,
+) -> Result<(), AppError> {
+    let all = categories::Entity::find().all(db).await?;
+    if !all.iter().any(|c| c.id == parent_id) {
+        return Err(AppError::BadRequest(format!(
+            "Parent category {parent_id} not found"
+        )));
+    }
+
+    if let Some(id) = id {
+        if parent_id == id || Tree::new(&all).is_descendant(parent_id, id) {
+            return Err(AppError::BadRequest(
+                "A category cannot be nested under itself or its subcategories".into(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn valid_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("Category name is required".into()));
+    }
+    Ok(name.to_string())
+}
+
+async fn ensure_unique(
+    db: &DatabaseConnection,
+    name: &str,
+    except: Option<i32>,
+) -> Result<(), AppError> {
+    let mut query = categories::Entity::find().filter(categories::Column::Name.eq(name));
+    if let Some(id) = except {
+        query = query.filter(categories::Column::Id.ne(id));
+    }
+
+    if query.one(db).await?.is_some() {
+        return Err(AppError::BadRequest(format!(
+            "Category '{name}' already exists"
+        )));
+    }
+    Ok(())
+}
@@ -1,22 +1,43 @@
 use axum::extract::State;
+use axum::http::StatusCode;
 use axum::Json;
 use sea_orm::DatabaseConnection;
 
+use crate::config::Config;
+use crate::entities::jobs;
 use crate::error::AppError;
+use crate::services;
+use crate::services::categorize;
 
+/// Start a background job categorizing all uncategorized transactions and
+/// return it; poll `GET /api/jobs/{id}` for progress. While a categorize job
+/// is already queued or running, that job is returned instead. Results below
+/// the auto-accept threshold land in the suggestion review queue.
 pub async fn categorize(
     State(db): State<DatabaseConnection>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let api_key = std::env::var("OPENAI_API_KEY")
-        .map_err(|_| AppError::BadRequest("OPENAI_API_KEY not configured".into()))?;
+    State(config): State<Config>,
+) -> Result<(StatusCode, Json<jobs::Model>), AppError> {
+    let categorizer = categorize::from_config(&db, &config.llm)
+        .await
+        .map_err(AppError::BadRequest)?;
+
+    if let Some(job) = services::jobs::active(&db, services::jobs::KIND_CATEGORIZE)
+        .await
+        .map_err(AppError::Internal)?
+    {
+        return Ok((StatusCode::OK, Json(job)));
+    }
 
-    let result = crate::services::categorize::categorize_uncategorized(&db, &api_key)
+    let job = services::jobs::create(&db, services::jobs::KIND_CATEGORIZE)
         .await
         .map_err(AppError::Internal)?;
 
-    Ok(Json(serde_json::json!({
-        "total": result.total,
-        "categorized": result.categorized,
-        "failed": result.failed,
-    })))
+    tokio::spawn(categorize::run_job(
+        db,
+        categorizer,
+        config.llm.clone(),
+        job.id,
+    ));
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
@@ -1,45 +1,207 @@
-use axum::extract::{Multipart, State};
-use axum::Json;
-use sea_orm::DatabaseConnection;
-use serde::Serialize;
+use axum::extract::{Multipart, Path, Query, State};
+use axum::{Extension, Json};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
 
+use crate::auth::CurrentUser;
+use crate::config::Config;
+use crate::entities::{accounts, categories, import_mappings};
 use crate::error::AppError;
 use crate::services;
+use crate::services::history::Actor;
+use crate::services::mapping::{KIND_ACCOUNT, KIND_CATEGORY};
 
 #[derive(Serialize)]
 pub struct ImportResponse {
     pub total_rows: i32,
     pub imported: i32,
     pub skipped: i32,
+    pub failed: i32,
+    pub files: Vec<FileImportResponse>,
+}
+
+#[derive(Serialize)]
+pub struct FileImportResponse {
+    pub filename: String,
+    pub total_rows: i32,
+    pub imported: i32,
+    pub skipped: i32,
+    pub error: Option<String>,
 }
 
 pub async fn upload(
     State(db): State<DatabaseConnection>,
+    State(config): State<Config>,
     mut multipart: Multipart,
 ) -> Result<Json<ImportResponse>, AppError> {
-    let field = multipart
+    let mut files = Vec::new();
+
+    while let Some(field) = multipart
         .next_field()
         .await
         .map_err(|e| AppError::BadRequest(format!("Multipart error: {e}")))?
-        .ok_or_else(|| AppError::BadRequest("No file provided".into()))?;
+    {
+        let filename = field.file_name().unwrap_or("upload.csv").to_string();
 
-    let filename = field.file_name().unwrap_or("upload.csv").to_string();
+        let bytes = field
+            .bytes()
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}")))?;
 
-    let bytes = field
-        .bytes()
-        .await
-        .map_err(|e| AppError::BadRequest(format!("Failed to read file: {e}")))?;
+        files.push((filename, bytes.to_vec()));
+    }
 
-    let content = String::from_utf8(bytes.to_vec())
-        .map_err(|e| AppError::BadRequest(format!("File is not valid UTF-8: {e}")))?;
+    if files.is_empty() {
+        return Err(AppError::BadRequest("No file provided".into()));
+    }
 
-    let result = services::import::import_file(&db, &filename, &content)
-        .await
-        .map_err(AppError::BadRequest)?;
+    let results = services::import::import_batch(&db, &config.archive_dir, files).await;
+
+    // Nothing went in: surface it as an error, like a failed single-file upload
+    if results.iter().all(|f| f.result.is_err()) {
+        let message = results
+            .iter()
+            .filter_map(|f| {
+                f.result
+                    .as_ref()
+                    .err()
+                    .map(|e| format!("{}: {}", f.filename, e))
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(AppError::BadRequest(message));
+    }
+
+    let files: Vec<FileImportResponse> = results
+        .into_iter()
+        .map(|f| match f.result {
+            Ok(r) => FileImportResponse {
+                filename: f.filename,
+                total_rows: r.total_rows,
+                imported: r.imported,
+                skipped: r.skipped,
+                error: None,
+            },
+            Err(e) => FileImportResponse {
+                filename: f.filename,
+                total_rows: 0,
+                imported: 0,
+                skipped: 0,
+                error: Some(e),
+            },
+        })
+        .collect();
 
     Ok(Json(ImportResponse {
-        total_rows: result.total_rows,
-        imported: result.imported,
-        skipped: result.skipped,
+        total_rows: files.iter().map(|f| f.total_rows).sum(),
+        imported: files.iter().map(|f| f.imported).sum(),
+        skipped: files.iter().map(|f| f.skipped).sum(),
+        failed: files.iter().filter(|f| f.error.is_some()).count() as i32,
+        files,
+    }))
+}
+
+// --- Mappings for exports from other finance apps ---
+
+#[derive(Debug, Deserialize)]
+pub struct MappingParams {
+    pub source: Option<String>,
+    pub kind: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMappingBody {
+    pub account_id: Option<i32>,
+    pub category_id: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct UpdateMappingResponse {
+    pub mapping: import_mappings::Model,
+    pub transactions_updated: u64,
+}
+
+pub async fn list_mappings(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<MappingParams>,
+) -> Result<Json<Vec<import_mappings::Model>>, AppError> {
+    let mut query = import_mappings::Entity::find();
+
+    if let Some(ref source) = params.source {
+        query = query.filter(import_mappings::Column::Source.eq(source.as_str()));
+    }
+    if let Some(ref kind) = params.kind {
+        query = query.filter(import_mappings::Column::Kind.eq(kind.as_str()));
+    }
+
+    let mappings = query
+        .order_by_asc(import_mappings::Column::Source)
+        .order_by_asc(import_mappings::Column::Kind)
+        .order_by_asc(import_mappings::Column::SourceValue)
+        .all(&db)
+        .await?;
+
+    Ok(Json(mappings))
+}
+
+/// Re-point a mapping. Account mappings apply to future imports; category
+/// mappings are also re-applied to the rows already imported from that source.
+pub async fn update_mapping(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+    Json(body): Json<UpdateMappingBody>,
+) -> Result<Json<UpdateMappingResponse>, AppError> {
+    let existing = import_mappings::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Mapping {id} not found")))?;
+
+    let mut active: import_mappings::ActiveModel = existing.clone().into();
+
+    match existing.kind.as_str() {
+        KIND_ACCOUNT => {
+            let account_id = body
+                .account_id
+                .ok_or_else(|| AppError::BadRequest("account_id is required".into()))?;
+            accounts::Entity::find_by_id(account_id)
+                .one(&db)
+                .await?
+                .ok_or_else(|| AppError::BadRequest(format!("Account {account_id} not found")))?;
+            active.account_id = Set(Some(account_id));
+        }
+        KIND_CATEGORY => {
+            if let Some(category_id) = body.category_id {
+                categories::Entity::find_by_id(category_id)
+                    .one(&db)
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::BadRequest(format!("Category {category_id} not found"))
+                    })?;
+            }
+            active.category_id = Set(body.category_id);
+        }
+        other => {
+            return Err(AppError::Internal(format!(
+                "Unknown mapping kind '{other}'"
+            )));
+        }
+    }
+
+    let mapping = active.update(&db).await?;
+
+    let transactions_updated = if mapping.kind == KIND_CATEGORY {
+        services::mapping::reapply_category(&db, &mapping, &Actor::new(&user.0, "import_mapping"))
+            .await
+            .map_err(AppError::Internal)?
+    } else {
+        0
+    };
+
+    Ok(Json(UpdateMappingResponse {
+        mapping,
+        transactions_updated,
     }))
 }
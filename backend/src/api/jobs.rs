@@ -0,0 +1,45 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QuerySelect};
+
+use crate::entities::jobs;
+use crate::error::AppError;
+use crate::services;
+
+/// The 50 most recent jobs.
+pub async fn list(
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<jobs::Model>>, AppError> {
+    let jobs = jobs::Entity::find()
+        .order_by_desc(jobs::Column::Id)
+        .limit(50)
+        .all(&db)
+        .await?;
+
+    Ok(Json(jobs))
+}
+
+pub async fn get_one(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<Json<jobs::Model>, AppError> {
+    let job = jobs::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Job {id} not found")))?;
+
+    Ok(Json(job))
+}
+
+/// Request cancellation; the job stops after its current batch.
+pub async fn cancel(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<Json<jobs::Model>, AppError> {
+    let job = services::jobs::request_cancel(&db, id)
+        .await
+        .map_err(AppError::Internal)?
+        .ok_or_else(|| AppError::NotFound(format!("Job {id} not found")))?;
+
+    Ok(Json(job))
+}
@@ -1,44 +1,227 @@
+use axum::extract::{DefaultBodyLimit, FromRef};
 use axum::{middleware, Router};
 use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Deserializer};
 use tower_http::cors::{Any, CorsLayer};
 
 use crate::config::Config;
+use crate::services::attachments::Store;
 
 pub mod accounts;
+pub mod admin;
+pub mod attachments;
+pub mod bank_categories;
 pub mod categories;
 pub mod categorize;
 pub mod import;
+pub mod jobs;
+pub mod rules;
 pub mod stats;
+pub mod suggestions;
+pub mod tags;
 pub mod transactions;
 
-pub fn create_router(pool: DatabaseConnection, config: Config) -> Router {
+/// Request body limit for statement uploads (several files or a ZIP at once).
+const UPLOAD_LIMIT: usize = 50 * 1024 * 1024;
+
+/// Room for multipart boundaries and headers around an uploaded file.
+const MULTIPART_OVERHEAD: usize = 64 * 1024;
+
+/// For `Option<Option<T>>` body fields with `#[serde(default)]`: `Some(None)`
+/// for an explicit `null`, `None` when the field is absent.
+pub(crate) fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Shared handler state. Handlers extract the pieces they need
+/// (`State<DatabaseConnection>`, `State<Config>`) via `FromRef`.
+#[derive(Clone)]
+pub struct AppState {
+    pub db: DatabaseConnection,
+    pub config: Config,
+    pub attachments: Store,
+}
+
+impl FromRef<AppState> for DatabaseConnection {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for Config {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for Store {
+    fn from_ref(state: &AppState) -> Self {
+        state.attachments.clone()
+    }
+}
+
+pub fn create_router(pool: DatabaseConnection, config: Config, attachments: Store) -> Router {
+    // Several attachments fit in one upload, and always one of the largest size
+    let attachment_limit = UPLOAD_LIMIT.max(
+        config
+            .attachments
+            .max_bytes
+            .saturating_add(MULTIPART_OVERHEAD),
+    );
+
     let api = Router::new()
-        .route("/api/transactions", axum::routing::get(transactions::list))
+        .route(
+            "/api/transactions",
+            axum::routing::get(transactions::list)
+                .post(transactions::create)
+                .patch(transactions::bulk_edit),
+        )
+        .route(
+            "/api/transactions/inbox",
+            axum::routing::get(transactions::inbox),
+        )
+        .route(
+            "/api/transactions/review",
+            axum::routing::post(transactions::review),
+        )
+        .route(
+            "/api/transactions/trash",
+            axum::routing::get(transactions::trash),
+        )
+        .route(
+            "/api/transactions/{id}/restore",
+            axum::routing::post(transactions::restore),
+        )
+        .route(
+            "/api/transactions/bulk",
+            axum::routing::post(transactions::bulk_create),
+        )
         .route(
             "/api/transactions/{id}",
-            axum::routing::get(transactions::get_one),
+            axum::routing::get(transactions::get_one)
+                .patch(transactions::update)
+                .delete(transactions::delete),
+        )
+        .route(
+            "/api/transactions/{id}/history",
+            axum::routing::get(transactions::history),
+        )
+        .route(
+            "/api/transactions/{id}/history/{entry_id}/revert",
+            axum::routing::post(transactions::revert),
         )
         .route(
             "/api/transactions/{id}/category",
             axum::routing::patch(transactions::update_category),
         )
-        .route("/api/import", axum::routing::post(import::upload))
-        .route("/api/categories", axum::routing::get(categories::list))
+        .route("/api/transactions/tags", axum::routing::post(tags::bulk))
+        .route(
+            "/api/transactions/{id}/tags",
+            axum::routing::get(tags::for_transaction),
+        )
+        .route(
+            "/api/transactions/{id}/notes",
+            axum::routing::patch(transactions::update_notes),
+        )
+        .route(
+            "/api/transactions/{id}/attachments",
+            axum::routing::get(attachments::list)
+                .post(attachments::upload)
+                .delete(attachments::delete_all)
+                .layer(DefaultBodyLimit::max(attachment_limit)),
+        )
+        .route(
+            "/api/attachments/{id}",
+            axum::routing::get(attachments::download).delete(attachments::delete),
+        )
+        .route(
+            "/api/transactions/{id}/splits",
+            axum::routing::get(transactions::list_splits)
+                .put(transactions::replace_splits)
+                .delete(transactions::delete_splits),
+        )
+        .route(
+            "/api/import",
+            axum::routing::post(import::upload).layer(DefaultBodyLimit::max(UPLOAD_LIMIT)),
+        )
+        .route(
+            "/api/import/mappings",
+            axum::routing::get(import::list_mappings),
+        )
+        .route(
+            "/api/import/mappings/{id}",
+            axum::routing::put(import::update_mapping),
+        )
+        .route(
+            "/api/rules",
+            axum::routing::get(rules::list).post(rules::create),
+        )
+        .route("/api/rules/apply", axum::routing::post(rules::apply))
+        .route(
+            "/api/rules/{id}",
+            axum::routing::put(rules::update).delete(rules::delete),
+        )
+        .route(
+            "/api/bank-categories/mappings",
+            axum::routing::get(bank_categories::list_mappings).put(bank_categories::upsert_mapping),
+        )
+        .route(
+            "/api/bank-categories/mappings/{id}",
+            axum::routing::delete(bank_categories::delete_mapping),
+        )
+        .route(
+            "/api/bank-categories/unmapped",
+            axum::routing::get(bank_categories::unmapped),
+        )
+        .route(
+            "/api/categories",
+            axum::routing::get(categories::list).post(categories::create),
+        )
+        .route("/api/categories/tree", axum::routing::get(categories::tree))
+        .route(
+            "/api/categories/{id}",
+            axum::routing::patch(categories::update).delete(categories::delete),
+        )
+        .route(
+            "/api/categories/{id}/merge",
+            axum::routing::post(categories::merge),
+        )
+        .route("/api/tags", axum::routing::get(tags::list))
+        .route("/api/tags/{id}", axum::routing::delete(tags::delete))
         .route("/api/accounts", axum::routing::get(accounts::list))
         .route("/api/stats/monthly", axum::routing::get(stats::monthly))
         .route(
             "/api/stats/categories",
             axum::routing::get(stats::by_category),
         )
+        .route("/api/stats/tags", axum::routing::get(stats::by_tag))
+        .route("/api/stats/accuracy", axum::routing::get(stats::accuracy))
         .route(
             "/api/categorize",
             axum::routing::post(categorize::categorize),
         )
+        .route("/api/suggestions", axum::routing::get(suggestions::list))
+        .route(
+            "/api/suggestions/review",
+            axum::routing::post(suggestions::review),
+        )
+        .route("/api/jobs", axum::routing::get(jobs::list))
+        .route("/api/jobs/{id}", axum::routing::get(jobs::get_one))
+        .route("/api/jobs/{id}/cancel", axum::routing::post(jobs::cancel))
+        .route("/api/admin/reparse", axum::routing::post(admin::reparse))
         .layer(middleware::from_fn_with_state(
-            config,
+            config.clone(),
             crate::auth::basic_auth,
         ))
-        .with_state(pool);
+        .with_state(AppState {
+            db: pool,
+            config,
+            attachments,
+        });
 
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -0,0 +1,182 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use rust_decimal::Decimal;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::CurrentUser;
+use crate::entities::{accounts, categories, category_rules};
+use crate::error::AppError;
+use crate::services;
+use crate::services::history::Actor;
+
+#[derive(Deserialize)]
+pub struct RuleBody {
+    pub name: String,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub counterparty: Option<String>,
+    pub description_regex: Option<String>,
+    pub amount_min: Option<Decimal>,
+    pub amount_max: Option<Decimal>,
+    pub account_id: Option<i32>,
+    pub bank_type: Option<String>,
+    pub bank_category: Option<String>,
+    pub category_id: i32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Serialize)]
+pub struct ApplyResponse {
+    pub checked: u64,
+    pub updated: u64,
+}
+
+/// Rules in evaluation order: highest priority first.
+pub async fn list(
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<category_rules::Model>>, AppError> {
+    let rules = category_rules::Entity::find()
+        .order_by_desc(category_rules::Column::Priority)
+        .order_by_asc(category_rules::Column::Id)
+        .all(&db)
+        .await?;
+
+    Ok(Json(rules))
+}
+
+pub async fn create(
+    State(db): State<DatabaseConnection>,
+    Json(body): Json<RuleBody>,
+) -> Result<(StatusCode, Json<category_rules::Model>), AppError> {
+    let mut active: category_rules::ActiveModel = Default::default();
+    fill(&db, &mut active, body).await?;
+
+    let rule = active.insert(&db).await?;
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+pub async fn update(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Json(body): Json<RuleBody>,
+) -> Result<Json<category_rules::Model>, AppError> {
+    let existing = category_rules::Entity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Rule {id} not found")))?;
+
+    let mut active: category_rules::ActiveModel = existing.into();
+    fill(&db, &mut active, body).await?;
+
+    let rule = active.update(&db).await?;
+    Ok(Json(rule))
+}
+
+/// Transactions categorised by the rule keep their category; they just no
+/// longer point at a rule.
+pub async fn delete(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    let result = category_rules::Entity::delete_by_id(id).exec(&db).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(format!("Rule {id} not found")));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Re-run all enabled rules over the existing transactions.
+pub async fn apply(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+) -> Result<Json<ApplyResponse>, AppError> {
+    let actor = Actor::new(&user.0, services::rules::SOURCE_RULE);
+    let result = services::rules::apply_existing(&db, &actor)
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(ApplyResponse {
+        checked: result.checked,
+        updated: result.updated,
+    }))
+}
+
+/// Validate a rule body and copy it onto the active model.
+async fn fill(
+    db: &DatabaseConnection,
+    active: &mut category_rules::ActiveModel,
+    body: RuleBody,
+) -> Result<(), AppError> {
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("name is required".into()));
+    }
+
+    let counterparty = non_empty(body.counterparty);
+    let description_regex = non_empty(body.description_regex);
+    let bank_type = non_empty(body.bank_type);
+    let bank_category = non_empty(body.bank_category);
+
+    if counterparty.is_none()
+        && description_regex.is_none()
+        && body.amount_min.is_none()
+        && body.amount_max.is_none()
+        && body.account_id.is_none()
+        && bank_type.is_none()
+        && bank_category.is_none()
+    {
+        return Err(AppError::BadRequest(
+            "A rule needs at least one condition".into(),
+        ));
+    }
+
+    if let Some(ref pattern) = description_regex {
+        services::rules::compile_regex(pattern).map_err(AppError::BadRequest)?;
+    }
+    if let (Some(min), Some(max)) = (body.amount_min, body.amount_max) {
+        if min > max {
+            return Err(AppError::BadRequest(
+                "amount_min must not exceed amount_max".into(),
+            ));
+        }
+    }
+
+    categories::Entity::find_by_id(body.category_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("Category {} not found", body.category_id)))?;
+    if let Some(account_id) = body.account_id {
+        accounts::Entity::find_by_id(account_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::BadRequest(format!("Account {account_id} not found")))?;
+    }
+
+    active.name = Set(name.to_string());
+    active.priority = Set(body.priority);
+    active.enabled = Set(body.enabled);
+    active.counterparty = Set(counterparty);
+    active.description_regex = Set(description_regex);
+    active.amount_min = Set(body.amount_min);
+    active.amount_max = Set(body.amount_max);
+    active.account_id = Set(body.account_id);
+    active.bank_type = Set(bank_type);
+    active.bank_category = Set(bank_category);
+    active.category_id = Set(body.category_id);
+
+    Ok(())
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
@@ -7,9 +7,15 @@ use sea_orm::{
     QueryFilter, QueryOrder, QuerySelect, RelationTrait,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::entities::{accounts, categories, transactions};
+use crate::entities::{
+    accounts, categories, tags, transaction_splits, transaction_tags, transactions,
+};
 use crate::error::AppError;
+use crate::services;
+use crate::services::accuracy::Report;
+use crate::services::categories::{roll_up, Totals, Tree};
 
 // --- Monthly ---
 
@@ -30,7 +36,7 @@ pub async fn monthly(
     State(db): State<DatabaseConnection>,
     Query(params): Query<MonthlyParams>,
 ) -> Result<Json<Vec<MonthlyRow>>, AppError> {
-    let mut condition = Condition::all();
+    let mut condition = Condition::all().add(transactions::Column::DeletedAt.is_null());
 
     if let Some(ref account) = params.account {
         condition = condition.add(accounts::Column::Name.eq(account.as_str()));
@@ -78,20 +84,39 @@ pub struct CategoryParams {
     pub date_from: Option<String>,
     pub date_to: Option<String>,
     pub account: Option<String>,
+    /// Count subcategories below this level (1 = top level) in their ancestor.
+    pub depth: Option<usize>,
+    /// Include each category's subcategories in its total. Parents and
+    /// children then overlap, so the rows no longer add up to the total spend.
+    #[serde(default)]
+    pub rollup: bool,
 }
 
-#[derive(Debug, Serialize, FromQueryResult)]
+#[derive(Debug, Serialize)]
 pub struct CategoryRow {
+    pub category_id: Option<i32>,
     pub category: Option<String>,
+    pub parent_id: Option<i32>,
+    /// 1 for top-level categories; absent for uncategorised spending.
+    pub depth: Option<usize>,
     pub total: Option<Decimal>,
     pub count: Option<i64>,
 }
 
+#[derive(Debug, FromQueryResult)]
+struct CategoryTotal {
+    category_id: Option<i32>,
+    total: Option<Decimal>,
+    count: Option<i64>,
+}
+
 pub async fn by_category(
     State(db): State<DatabaseConnection>,
     Query(params): Query<CategoryParams>,
 ) -> Result<Json<Vec<CategoryRow>>, AppError> {
-    let mut condition = Condition::all().add(transactions::Column::Amount.lt(Decimal::ZERO));
+    let mut condition = Condition::all()
+        .add(transactions::Column::Amount.lt(Decimal::ZERO))
+        .add(transactions::Column::DeletedAt.is_null());
 
     if let Some(ref account) = params.account {
         condition = condition.add(accounts::Column::Name.eq(account.as_str()));
@@ -107,19 +132,150 @@ pub async fn by_category(
         }
     }
 
-    let rows = transactions::Entity::find()
+    // Split transactions count through their splits, the rest whole
+    let whole = transactions::Entity::find()
         .select_only()
-        .column_as(categories::Column::Name, "category")
+        .column(transactions::Column::CategoryId)
         .column_as(transactions::Column::Amount.sum(), "total")
         .column_as(transactions::Column::Id.count(), "count")
         .join(JoinType::InnerJoin, transactions::Relation::Account.def())
-        .join(JoinType::LeftJoin, transactions::Relation::Category.def())
+        .filter(condition.clone())
+        .filter(Expr::cust(
+            "NOT EXISTS (SELECT 1 FROM transaction_splits s WHERE s.transaction_id = transactions.id)",
+        ))
+        .group_by(transactions::Column::CategoryId)
+        .into_model::<CategoryTotal>()
+        .all(&db)
+        .await?;
+
+    let split = transaction_splits::Entity::find()
+        .select_only()
+        .column(transaction_splits::Column::CategoryId)
+        .column_as(transaction_splits::Column::Amount.sum(), "total")
+        .column_as(transaction_splits::Column::Id.count(), "count")
+        .join(
+            JoinType::InnerJoin,
+            transaction_splits::Relation::Transaction.def(),
+        )
+        .join(JoinType::InnerJoin, transactions::Relation::Account.def())
+        .filter(condition)
+        .group_by(transaction_splits::Column::CategoryId)
+        .into_model::<CategoryTotal>()
+        .all(&db)
+        .await?;
+
+    let mut totals = Totals::new();
+    for row in whole.into_iter().chain(split) {
+        let entry = totals.entry(row.category_id).or_default();
+        entry.0 += row.total.unwrap_or_default();
+        entry.1 += row.count.unwrap_or_default();
+    }
+
+    let all = categories::Entity::find().all(&db).await?;
+    let tree = Tree::new(&all);
+    let by_id: HashMap<i32, &categories::Model> = all.iter().map(|c| (c.id, c)).collect();
+
+    let mut rows: Vec<CategoryRow> = roll_up(&totals, &tree, params.depth, params.rollup)
+        .into_iter()
+        .map(|(category_id, (total, count))| {
+            let category = category_id.and_then(|id| by_id.get(&id));
+            CategoryRow {
+                category_id,
+                category: category.map(|c| c.name.clone()),
+                parent_id: category.and_then(|c| c.parent_id),
+                depth: category_id.map(|id| tree.depth(id)),
+                total: Some(total),
+                count: Some(count),
+            }
+        })
+        .collect();
+    rows.sort_by_key(|row| row.total);
+
+    Ok(Json(rows))
+}
+
+// --- Tags ---
+
+#[derive(Debug, Deserialize)]
+pub struct TagParams {
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub account: Option<String>,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct TagRow {
+    pub tag: String,
+    pub income: Option<Decimal>,
+    pub expense: Option<Decimal>,
+    pub count: Option<i64>,
+}
+
+/// Income and expense per tag. A transaction with several tags counts
+/// under each of them.
+pub async fn by_tag(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<TagParams>,
+) -> Result<Json<Vec<TagRow>>, AppError> {
+    let mut condition = Condition::all().add(transactions::Column::DeletedAt.is_null());
+
+    if let Some(ref account) = params.account {
+        condition = condition.add(accounts::Column::Name.eq(account.as_str()));
+    }
+    if let Some(ref date_from) = params.date_from {
+        if let Ok(d) = chrono::NaiveDate::parse_from_str(date_from, "%Y-%m-%d") {
+            condition = condition.add(transactions::Column::TransactionDate.gte(d));
+        }
+    }
+    if let Some(ref date_to) = params.date_to {
+        if let Ok(d) = chrono::NaiveDate::parse_from_str(date_to, "%Y-%m-%d") {
+            condition = condition.add(transactions::Column::TransactionDate.lte(d));
+        }
+    }
+
+    let income_expr =
+        Expr::cust("SUM(CASE WHEN transactions.amount > 0 THEN transactions.amount ELSE 0 END)");
+    let expense_expr =
+        Expr::cust("SUM(CASE WHEN transactions.amount < 0 THEN transactions.amount ELSE 0 END)");
+
+    let rows = transaction_tags::Entity::find()
+        .select_only()
+        .column_as(tags::Column::Name, "tag")
+        .column_as(income_expr, "income")
+        .column_as(expense_expr, "expense")
+        .column_as(transactions::Column::Id.count(), "count")
+        .join(JoinType::InnerJoin, transaction_tags::Relation::Tag.def())
+        .join(
+            JoinType::InnerJoin,
+            transaction_tags::Relation::Transaction.def(),
+        )
+        .join(JoinType::InnerJoin, transactions::Relation::Account.def())
         .filter(condition)
-        .group_by(categories::Column::Name)
-        .order_by_asc(Expr::cust("total"))
-        .into_model::<CategoryRow>()
+        .group_by(tags::Column::Name)
+        .order_by_asc(tags::Column::Name)
+        .into_model::<TagRow>()
         .all(&db)
         .await?;
 
     Ok(Json(rows))
 }
+
+// --- Categorization accuracy ---
+
+#[derive(Debug, Deserialize)]
+pub struct AccuracyParams {
+    pub source: Option<String>,
+}
+
+/// How often each automatic category source (AI, rules, bank mappings, the
+/// classifier) agrees with later manual overrides.
+pub async fn accuracy(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<AccuracyParams>,
+) -> Result<Json<Report>, AppError> {
+    let report = services::accuracy::report(&db, params.source.as_deref())
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(report))
+}
@@ -0,0 +1,113 @@
+use axum::extract::{Query, State};
+use axum::{Extension, Json};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::CurrentUser;
+use crate::entities::{categories, category_suggestions, transactions};
+use crate::error::AppError;
+use crate::models::Transaction;
+use crate::services;
+use crate::services::history::Actor;
+use crate::services::suggestions::{Review, STATUS_PENDING};
+
+#[derive(Deserialize)]
+pub struct ListParams {
+    /// pending (default), accepted, edited or rejected
+    pub status: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SuggestionRow {
+    #[serde(flatten)]
+    pub suggestion: category_suggestions::Model,
+    pub transaction: Option<Transaction>,
+}
+
+#[derive(Deserialize)]
+pub struct EditItem {
+    pub id: i32,
+    pub category_id: i32,
+}
+
+#[derive(Deserialize)]
+pub struct ReviewBody {
+    #[serde(default)]
+    pub accept: Vec<i32>,
+    #[serde(default)]
+    pub reject: Vec<i32>,
+    #[serde(default)]
+    pub edit: Vec<EditItem>,
+}
+
+#[derive(Serialize)]
+pub struct ReviewResponse {
+    pub accepted: u64,
+    pub edited: u64,
+    pub rejected: u64,
+    pub skipped: Vec<i32>,
+}
+
+/// Suggestions with their transactions, least confident first.
+pub async fn list(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<SuggestionRow>>, AppError> {
+    let status = params.status.as_deref().unwrap_or(STATUS_PENDING);
+
+    let rows = category_suggestions::Entity::find()
+        .find_also_related(transactions::Entity)
+        .filter(category_suggestions::Column::Status.eq(status))
+        .filter(transactions::Column::DeletedAt.is_null())
+        .order_by_asc(category_suggestions::Column::Confidence)
+        .order_by_asc(category_suggestions::Column::Id)
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(|(suggestion, transaction)| SuggestionRow {
+            suggestion,
+            transaction,
+        })
+        .collect();
+
+    Ok(Json(rows))
+}
+
+/// Accept, reject or edit any number of pending suggestions at once.
+pub async fn review(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Json(body): Json<ReviewBody>,
+) -> Result<Json<ReviewResponse>, AppError> {
+    for item in &body.edit {
+        categories::Entity::find_by_id(item.category_id)
+            .one(&db)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest(format!("Category {} not found", item.category_id))
+            })?;
+    }
+
+    let result = services::suggestions::review(
+        &db,
+        Review {
+            accept: body.accept,
+            reject: body.reject,
+            edit: body
+                .edit
+                .into_iter()
+                .map(|e| (e.id, e.category_id))
+                .collect(),
+        },
+        &Actor::new(&user.0, "suggestion"),
+    )
+    .await
+    .map_err(AppError::Internal)?;
+
+    Ok(Json(ReviewResponse {
+        accepted: result.accepted,
+        edited: result.edited,
+        rejected: result.rejected,
+        skipped: result.skipped,
+    }))
+}
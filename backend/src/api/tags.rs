@@ -0,0 +1,146 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, FromQueryResult, JoinType, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{tags, transaction_tags, transactions};
+use crate::error::AppError;
+use crate::services;
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct TagRow {
+    pub id: i32,
+    pub name: String,
+    pub count: i64,
+}
+
+#[derive(Deserialize)]
+pub struct BulkBody {
+    pub transaction_ids: Vec<i32>,
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct BulkResponse {
+    pub added: u64,
+    pub removed: u64,
+}
+
+/// All tags with the number of transactions (outside the trash) carrying them.
+pub async fn list(State(db): State<DatabaseConnection>) -> Result<Json<Vec<TagRow>>, AppError> {
+    let rows = tags::Entity::find()
+        .select_only()
+        .column(tags::Column::Id)
+        .column(tags::Column::Name)
+        .column_as(
+            Expr::cust("COUNT(transactions.id) FILTER (WHERE transactions.deleted_at IS NULL)"),
+            "count",
+        )
+        .join(JoinType::LeftJoin, tags::Relation::TransactionTags.def())
+        .join(
+            JoinType::LeftJoin,
+            transaction_tags::Relation::Transaction.def(),
+        )
+        .group_by(tags::Column::Id)
+        .order_by_asc(tags::Column::Name)
+        .into_model::<TagRow>()
+        .all(&db)
+        .await?;
+
+    Ok(Json(rows))
+}
+
+/// Delete a tag and untag every transaction carrying it.
+pub async fn delete(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    let result = tags::Entity::delete_by_id(id).exec(&db).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(format!("Tag {id} not found")));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Tags of a single transaction.
+pub async fn for_transaction(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<tags::Model>>, AppError> {
+    let tags = tags::Entity::find()
+        .join(JoinType::InnerJoin, tags::Relation::TransactionTags.def())
+        .filter(transaction_tags::Column::TransactionId.eq(id))
+        .order_by_asc(tags::Column::Name)
+        .all(&db)
+        .await?;
+
+    Ok(Json(tags))
+}
+
+/// Add and/or remove tags on many transactions at once. Tags named in `add`
+/// are created as needed; names are normalised (lowercase, dashes).
+pub async fn bulk(
+    State(db): State<DatabaseConnection>,
+    Json(body): Json<BulkBody>,
+) -> Result<Json<BulkResponse>, AppError> {
+    if body.transaction_ids.is_empty() {
+        return Err(AppError::BadRequest("No transactions provided".into()));
+    }
+    if body.add.is_empty() && body.remove.is_empty() {
+        return Err(AppError::BadRequest("No tags to add or remove".into()));
+    }
+
+    let normalize = |names: &[String]| -> Result<Vec<String>, AppError> {
+        names
+            .iter()
+            .map(|n| services::tags::normalize(n).map_err(AppError::BadRequest))
+            .collect()
+    };
+    let add = normalize(&body.add)?;
+    let remove = normalize(&body.remove)?;
+    if let Some(name) = add.iter().find(|n| remove.contains(n)) {
+        return Err(AppError::BadRequest(format!(
+            "Tag '{name}' is both added and removed"
+        )));
+    }
+
+    let mut ids = body.transaction_ids;
+    ids.sort_unstable();
+    ids.dedup();
+    let found = transactions::Entity::find()
+        .filter(transactions::Column::Id.is_in(ids.iter().copied()))
+        .count(&db)
+        .await?;
+    if found != ids.len() as u64 {
+        return Err(AppError::BadRequest(format!(
+            "{} of {} transactions not found",
+            ids.len() as u64 - found,
+            ids.len()
+        )));
+    }
+
+    let ids_of = |tags: Vec<tags::Model>| tags.into_iter().map(|t| t.id).collect::<Vec<_>>();
+    let added_tags = services::tags::ensure(&db, &add)
+        .await
+        .map_err(AppError::Internal)?;
+    let removed_tags = services::tags::find(&db, &remove)
+        .await
+        .map_err(AppError::Internal)?;
+
+    let added = services::tags::add(&db, &ids, &ids_of(added_tags))
+        .await
+        .map_err(AppError::Internal)?;
+    let removed = services::tags::remove(&db, &ids, &ids_of(removed_tags))
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(BulkResponse { added, removed }))
+}
@@ -1,21 +1,33 @@
 use axum::extract::{Json, Path, Query, State};
+use axum::http::StatusCode;
+use axum::Extension;
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, JoinType,
-    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, JoinType, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set, TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::entities::{accounts, transactions};
+use crate::auth::CurrentUser;
+use crate::entities::{
+    accounts, categories, category_rules, transaction_history, transaction_splits, transactions,
+};
 use crate::error::AppError;
 use crate::models::Transaction;
+use crate::services;
+use crate::services::attachments::Store;
+use crate::services::bulk_edit::{Edit, Outcome};
+use crate::services::filter::TransactionFilter;
+use crate::services::history::Actor;
+use crate::services::import::RowOutcome;
+use crate::services::manual::{self, NewTransaction};
+use crate::services::splits::NewSplit;
 
+/// Paging and sorting; the filter itself is a [`TransactionFilter`] read
+/// from the same query string.
 #[derive(Debug, Deserialize)]
 pub struct ListParams {
-    pub account: Option<String>,
-    pub category_id: Option<i32>,
-    pub date_from: Option<String>,
-    pub date_to: Option<String>,
-    pub search: Option<String>,
     pub page: Option<u64>,
     pub per_page: Option<u64>,
     pub sort_by: Option<String>,
@@ -30,44 +42,153 @@ pub struct ListResponse {
     pub per_page: u64,
 }
 
+/// A transaction entered by hand, e.g. cash spending. The currency
+/// defaults to the account's.
+#[derive(Deserialize)]
+pub struct CreateBody {
+    pub account_id: i32,
+    pub transaction_date: NaiveDate,
+    pub amount: Decimal,
+    pub currency: Option<String>,
+    pub description: String,
+    pub counterparty: Option<String>,
+    pub category_id: Option<i32>,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteParams {
+    #[serde(default)]
+    pub permanent: bool,
+}
+
+/// Fields left out are unchanged; `"counterparty": null` clears it.
+#[derive(Deserialize)]
+pub struct UpdateBody {
+    pub account_id: Option<i32>,
+    pub transaction_date: Option<NaiveDate>,
+    pub amount: Option<Decimal>,
+    pub currency: Option<String>,
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "super::present")]
+    pub counterparty: Option<Option<String>>,
+}
+
 #[derive(Deserialize)]
 pub struct UpdateCategoryBody {
     pub category_id: Option<i32>,
+    /// Save the suggested rule for this merchant.
+    #[serde(default)]
+    pub create_rule: bool,
+    /// Also recategorise the merchant's other uncategorised or AI-categorised rows.
+    #[serde(default)]
+    pub apply_to_similar: bool,
+}
+
+#[derive(Serialize)]
+pub struct UpdateCategoryResponse {
+    #[serde(flatten)]
+    pub transaction: Transaction,
+    /// Rule that would categorise this merchant from now on (unless created).
+    pub suggested_rule: Option<RuleSuggestion>,
+    pub created_rule: Option<category_rules::Model>,
+    /// Other uncategorised or AI-categorised rows from the same merchant.
+    pub similar: usize,
+    pub recategorized: u64,
+}
+
+#[derive(Serialize)]
+pub struct RuleSuggestion {
+    pub name: String,
+    pub counterparty: String,
+    pub category_id: i32,
+    pub priority: i32,
+}
+
+#[derive(Deserialize)]
+pub struct NotesBody {
+    pub notes: Option<String>,
+}
+
+/// Select rows by `ids` or by `filter` (the list filter; it may not be
+/// empty). Fields left out are unchanged; `null` clears the category or notes.
+#[derive(Deserialize)]
+pub struct BulkEditBody {
+    pub ids: Option<Vec<i32>>,
+    pub filter: Option<TransactionFilter>,
+    #[serde(default, deserialize_with = "super::present")]
+    pub category_id: Option<Option<i32>>,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+    #[serde(default, deserialize_with = "super::present")]
+    pub notes: Option<Option<String>>,
+    pub review_status: Option<String>,
+    /// Only report what would change.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InboxParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    /// List flagged transactions instead of new ones.
+    #[serde(default)]
+    pub flagged: bool,
+}
+
+/// Select rows by `ids` or by `filter`, as for a bulk edit. The status
+/// defaults to `reviewed`.
+#[derive(Deserialize)]
+pub struct ReviewBody {
+    pub ids: Option<Vec<i32>>,
+    pub filter: Option<TransactionFilter>,
+    pub status: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReviewResponse {
+    pub changed: usize,
+    /// New transactions still waiting in the inbox.
+    pub remaining: u64,
+    /// The oldest of them, to show next.
+    pub next: Option<Transaction>,
+}
+
+#[derive(Deserialize)]
+pub struct SplitsBody {
+    pub splits: Vec<NewSplit>,
+}
+
+#[derive(Serialize)]
+pub struct BulkResponse {
+    pub total_rows: usize,
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub rows: Vec<BulkRow>,
+}
+
+#[derive(Serialize)]
+pub struct BulkRow {
+    pub index: usize,
+    pub status: &'static str,
+    pub id: Option<i32>,
+    pub hash: Option<String>,
+    pub error: Option<String>,
 }
 
 pub async fn list(
     State(db): State<DatabaseConnection>,
+    Query(filter): Query<TransactionFilter>,
     Query(params): Query<ListParams>,
 ) -> Result<Json<ListResponse>, AppError> {
     let page = params.page.unwrap_or(1).max(1);
     let per_page = params.per_page.unwrap_or(50).clamp(1, 200);
 
-    let mut condition = Condition::all();
-
-    if let Some(ref account) = params.account {
-        condition = condition.add(accounts::Column::Name.eq(account.as_str()));
-    }
-    if let Some(category_id) = params.category_id {
-        condition = condition.add(transactions::Column::CategoryId.eq(category_id));
-    }
-    if let Some(ref date_from) = params.date_from {
-        if let Ok(d) = chrono::NaiveDate::parse_from_str(date_from, "%Y-%m-%d") {
-            condition = condition.add(transactions::Column::TransactionDate.gte(d));
-        }
-    }
-    if let Some(ref date_to) = params.date_to {
-        if let Ok(d) = chrono::NaiveDate::parse_from_str(date_to, "%Y-%m-%d") {
-            condition = condition.add(transactions::Column::TransactionDate.lte(d));
-        }
-    }
-    if let Some(ref search) = params.search {
-        let pattern = format!("%{search}%");
-        condition = condition.add(
-            Condition::any()
-                .add(transactions::Column::Description.like(&pattern))
-                .add(transactions::Column::Counterparty.like(&pattern)),
-        );
-    }
+    let condition = filter.condition().map_err(AppError::BadRequest)?;
 
     let base_query = transactions::Entity::find()
         .join(JoinType::InnerJoin, transactions::Relation::Account.def())
@@ -130,21 +251,667 @@ pub async fn get_one(
     Ok(Json(tx))
 }
 
+/// Record a transaction by hand on any account.
+pub async fn create(
+    State(db): State<DatabaseConnection>,
+    Json(body): Json<CreateBody>,
+) -> Result<(StatusCode, Json<Transaction>), AppError> {
+    let account = find_account(&db, body.account_id).await?;
+    let description = valid_description(&body.description)?;
+    if body.amount.is_zero() {
+        return Err(AppError::BadRequest("Amount must not be zero".into()));
+    }
+    if let Some(category_id) = body.category_id {
+        if categories::Entity::find_by_id(category_id)
+            .one(&db)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::BadRequest(format!(
+                "Category {category_id} not found"
+            )));
+        }
+    }
+
+    let new = NewTransaction {
+        account_id: account.id,
+        transaction_date: body.transaction_date,
+        amount: body.amount,
+        currency: body
+            .currency
+            .map(|c| c.trim().to_uppercase())
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| account.currency.clone()),
+        description,
+        counterparty: body.counterparty.filter(|c| !c.trim().is_empty()),
+        category_id: body.category_id,
+        notes: body.notes.filter(|n| !n.trim().is_empty()),
+    };
+    let tx = new.into_active_model(&account.name).insert(&db).await?;
+
+    Ok((StatusCode::CREATED, Json(tx)))
+}
+
+/// Ingest an array of normalised `ParsedTransaction` objects from scripts
+/// (cash logs, reimbursements, scraped orders) with per-row results.
+pub async fn bulk_create(
+    State(db): State<DatabaseConnection>,
+    Json(rows): Json<Vec<serde_json::Value>>,
+) -> Result<Json<BulkResponse>, AppError> {
+    if rows.is_empty() {
+        return Err(AppError::BadRequest("No transactions provided".into()));
+    }
+
+    let results = services::import::import_rows(&db, "api/transactions/bulk", rows)
+        .await
+        .map_err(AppError::Internal)?;
+
+    let rows: Vec<BulkRow> = results
+        .into_iter()
+        .map(|r| {
+            let (status, id, error) = match r.outcome {
+                Ok(RowOutcome::Imported(id)) => ("imported", Some(id), None),
+                Ok(RowOutcome::Duplicate(id)) => ("duplicate", Some(id), None),
+                Err(e) => ("error", None, Some(e)),
+            };
+            BulkRow {
+                index: r.index,
+                status,
+                id,
+                hash: r.hash,
+                error,
+            }
+        })
+        .collect();
+
+    let count = |status: &str| rows.iter().filter(|r| r.status == status).count();
+
+    Ok(Json(BulkResponse {
+        total_rows: rows.len(),
+        imported: count("imported"),
+        skipped: count("duplicate"),
+        failed: count("error"),
+        rows,
+    }))
+}
+
+/// Edit a transaction's date, description or counterparty, and for manual
+/// transactions also the amount, currency and account. On imported rows the
+/// bank's values stay in `raw_data.original` and survive a reparse.
+pub async fn update(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+    Json(body): Json<UpdateBody>,
+) -> Result<Json<Transaction>, AppError> {
+    let tx = find(&db, id).await?;
+    if let Some(account_id) = body.account_id {
+        find_account(&db, account_id).await?;
+    }
+
+    let edit = manual::Edit {
+        transaction_date: body.transaction_date,
+        description: body
+            .description
+            .as_deref()
+            .map(valid_description)
+            .transpose()?,
+        counterparty: body
+            .counterparty
+            .map(|c| c.filter(|c| !c.trim().is_empty())),
+        amount: body.amount,
+        currency: body.currency.map(|c| c.trim().to_uppercase()),
+        account_id: body.account_id,
+    };
+    if edit.amount.is_some_and(|a| a.is_zero()) {
+        return Err(AppError::BadRequest("Amount must not be zero".into()));
+    }
+    if edit.amount.is_some_and(|a| a != tx.amount) {
+        ensure_unsplit(&db, id).await?;
+    }
+
+    let active = manual::edit(&tx, edit).map_err(AppError::BadRequest)?;
+    if !active.is_changed() {
+        return Ok(Json(tx));
+    }
+    let updated = active.update(&db).await?;
+    services::history::record_update(&db, &Actor::new(&user.0, "edit"), &tx, &updated)
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(updated))
+}
+
+/// Move a transaction to the trash. It keeps its hash, so importing its
+/// statement again won't bring it back. `?permanent=true` deletes a manual
+/// transaction for good, with its splits, tags, history and attachments.
+pub async fn delete(
+    State(db): State<DatabaseConnection>,
+    State(store): State<Store>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+    Query(params): Query<DeleteParams>,
+) -> Result<StatusCode, AppError> {
+    let tx = find(&db, id).await?;
+
+    if !params.permanent {
+        if tx.deleted_at.is_none() {
+            set_deleted(&db, tx, true, &Actor::new(&user.0, "delete")).await?;
+        }
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
+    if tx.source != manual::SOURCE_MANUAL {
+        return Err(AppError::BadRequest(format!(
+            "Transaction {id} was imported and would come back with the next import; move it to the trash instead"
+        )));
+    }
+    services::attachments::remove_all(&db, &store, id)
+        .await
+        .map_err(AppError::Internal)?;
+    transactions::Entity::delete_by_id(id).exec(&db).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Transactions in the trash, most recently deleted first.
+pub async fn trash(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<ListResponse>, AppError> {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(50).clamp(1, 200);
+
+    let query = transactions::Entity::find().filter(transactions::Column::DeletedAt.is_not_null());
+    let total = query.clone().count(&db).await?;
+    let data = query
+        .order_by_desc(transactions::Column::DeletedAt)
+        .order_by_desc(transactions::Column::Id)
+        .paginate(&db, per_page)
+        .fetch_page(page - 1)
+        .await?;
+
+    Ok(Json(ListResponse {
+        data,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+/// Take a transaction back out of the trash.
+pub async fn restore(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+) -> Result<Json<Transaction>, AppError> {
+    let tx = find(&db, id).await?;
+    if tx.deleted_at.is_none() {
+        return Ok(Json(tx));
+    }
+
+    let restored = set_deleted(&db, tx, false, &Actor::new(&user.0, "restore")).await?;
+    Ok(Json(restored))
+}
+
+async fn set_deleted(
+    db: &DatabaseConnection,
+    tx: transactions::Model,
+    deleted: bool,
+    actor: &Actor,
+) -> Result<transactions::Model, AppError> {
+    let mut active: transactions::ActiveModel = tx.clone().into();
+    active.deleted_at = Set(deleted.then(Utc::now));
+    let updated = active.update(db).await?;
+
+    services::history::record_update(db, actor, &tx, &updated)
+        .await
+        .map_err(AppError::Internal)?;
+    Ok(updated)
+}
+
+/// Set a transaction's category by hand. The response suggests a rule for the
+/// merchant and counts its other rows still lacking a real category; pass
+/// `create_rule` / `apply_to_similar` to act on them in the same call.
 pub async fn update_category(
     State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
     Path(id): Path<i32>,
     Json(body): Json<UpdateCategoryBody>,
-) -> Result<Json<Transaction>, AppError> {
-    // Verify it exists
-    let existing = transactions::Entity::find_by_id(id)
-        .one(&db)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Transaction {id} not found")))?;
+) -> Result<Json<UpdateCategoryResponse>, AppError> {
+    let actor = Actor::new(&user.0, "manual");
+
+    let existing = find(&db, id).await?;
+    if existing.deleted_at.is_some() {
+        return Err(AppError::BadRequest(format!(
+            "Transaction {id} is in the trash; restore it first"
+        )));
+    }
+    if let Some(category_id) = body.category_id {
+        let category = categories::Entity::find_by_id(category_id)
+            .one(&db)
+            .await?
+            .ok_or_else(|| AppError::BadRequest(format!("Category {category_id} not found")))?;
+        if category.archived_at.is_some() {
+            return Err(AppError::BadRequest(format!(
+                "Category '{}' is archived",
+                category.name
+            )));
+        }
+    }
+
+    // The override, the change and its history are written together
+    let txn = db.begin().await?;
+    services::accuracy::record_overrides(&txn, std::slice::from_ref(&existing), body.category_id)
+        .await
+        .map_err(AppError::Internal)?;
 
-    let mut active: transactions::ActiveModel = existing.into();
+    let mut active: transactions::ActiveModel = existing.clone().into();
     active.category_id = Set(body.category_id);
     active.category_source = Set(Some("manual".to_string()));
+    active.category_rule_id = Set(None);
+
+    let updated = active.update(&txn).await?;
+    services::history::record_update(&txn, &actor, &existing, &updated)
+        .await
+        .map_err(AppError::Internal)?;
+    txn.commit().await?;
+
+    let mut response = UpdateCategoryResponse {
+        transaction: updated,
+        suggested_rule: None,
+        created_rule: None,
+        similar: 0,
+        recategorized: 0,
+    };
+
+    let Some(category_id) = body.category_id else {
+        return Ok(Json(response));
+    };
+
+    services::merchants::remember(&db, &response.transaction, category_id)
+        .await
+        .map_err(AppError::Internal)?;
+
+    let suggestion = services::rules::suggest(&db, &response.transaction)
+        .await
+        .map_err(AppError::Internal)?;
+    let similar = services::rules::find_similar(&db, &response.transaction)
+        .await
+        .map_err(AppError::Internal)?;
+
+    match suggestion {
+        Some(suggestion) if body.create_rule => {
+            let rule = services::rules::create_from(&db, &suggestion)
+                .await
+                .map_err(AppError::Internal)?;
+            response.created_rule = Some(rule);
+        }
+        Some(suggestion) => {
+            response.suggested_rule = Some(RuleSuggestion {
+                name: suggestion.name,
+                counterparty: suggestion.counterparty,
+                category_id: suggestion.category_id,
+                priority: suggestion.priority,
+            });
+        }
+        None => {}
+    }
+
+    response.similar = similar.len();
+    if body.apply_to_similar {
+        services::accuracy::record_overrides(&db, &similar, Some(category_id))
+            .await
+            .map_err(AppError::Internal)?;
+        response.recategorized = services::rules::recategorize(
+            &db,
+            &similar,
+            category_id,
+            response.created_rule.as_ref(),
+            &actor,
+        )
+        .await
+        .map_err(AppError::Internal)?;
+    }
+
+    Ok(Json(response))
+}
+
+/// Set the category, tags, notes and/or reviewed status of many transactions
+/// in one database transaction. Setting a category works like
+/// [`update_category`] on each row: it becomes a manual override.
+pub async fn bulk_edit(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Json(body): Json<BulkEditBody>,
+) -> Result<Json<Outcome>, AppError> {
+    let normalize = |names: Vec<String>| {
+        names
+            .iter()
+            .map(|n| services::tags::normalize(n))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::BadRequest)
+    };
+    let edit = Edit {
+        category_id: body.category_id,
+        add_tags: normalize(body.add_tags)?,
+        remove_tags: normalize(body.remove_tags)?,
+        notes: body
+            .notes
+            .map(|notes| notes.filter(|n| !n.trim().is_empty())),
+        review_status: body
+            .review_status
+            .as_deref()
+            .map(services::review::parse)
+            .transpose()
+            .map_err(AppError::BadRequest)?,
+    };
+    if edit.is_empty() {
+        return Err(AppError::BadRequest("Nothing to change".into()));
+    }
+    if let Some(Some(category_id)) = edit.category_id {
+        if categories::Entity::find_by_id(category_id)
+            .one(&db)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::BadRequest(format!(
+                "Category {category_id} not found"
+            )));
+        }
+    }
+
+    let rows = select(&db, body.ids, body.filter).await?;
 
+    let outcome = services::bulk_edit::run(
+        &db,
+        rows,
+        &edit,
+        body.dry_run,
+        &Actor::new(&user.0, "bulk_edit"),
+    )
+    .await
+    .map_err(AppError::Internal)?;
+
+    Ok(Json(outcome))
+}
+
+/// Transactions nobody has reviewed yet (or with `?flagged=true`, the
+/// flagged ones), oldest first. Takes the list filter to narrow it down.
+pub async fn inbox(
+    State(db): State<DatabaseConnection>,
+    Query(filter): Query<TransactionFilter>,
+    Query(params): Query<InboxParams>,
+) -> Result<Json<ListResponse>, AppError> {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(50).clamp(1, 200);
+
+    let status = if params.flagged {
+        services::review::FLAGGED
+    } else {
+        services::review::NEW
+    };
+    let condition = filter.condition().map_err(AppError::BadRequest)?;
+    let query = services::review::inbox(status)
+        .join(JoinType::InnerJoin, transactions::Relation::Account.def())
+        .filter(condition);
+
+    let total = query.clone().count(&db).await?;
+    let data = query.paginate(&db, per_page).fetch_page(page - 1).await?;
+
+    Ok(Json(ListResponse {
+        data,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+/// Set the review status of many transactions at once and return what is
+/// left in the inbox, so a reviewer can keep going from the keyboard.
+pub async fn review(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Json(body): Json<ReviewBody>,
+) -> Result<Json<ReviewResponse>, AppError> {
+    let status = match body.status {
+        Some(ref status) => services::review::parse(status).map_err(AppError::BadRequest)?,
+        None => services::review::REVIEWED,
+    };
+    let rows = select(&db, body.ids, body.filter).await?;
+
+    let edit = Edit {
+        review_status: Some(status),
+        ..Default::default()
+    };
+    let outcome = services::bulk_edit::run(&db, rows, &edit, false, &Actor::new(&user.0, "review"))
+        .await
+        .map_err(AppError::Internal)?;
+
+    let inbox = services::review::inbox(services::review::NEW);
+    Ok(Json(ReviewResponse {
+        changed: outcome.changed,
+        remaining: inbox.clone().count(&db).await?,
+        next: inbox.one(&db).await?,
+    }))
+}
+
+/// Set or clear (`null` or blank) a transaction's free-text notes.
+pub async fn update_notes(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+    Json(body): Json<NotesBody>,
+) -> Result<Json<Transaction>, AppError> {
+    let existing = find(&db, id).await?;
+
+    let mut active: transactions::ActiveModel = existing.clone().into();
+    active.notes = Set(body.notes.filter(|n| !n.trim().is_empty()));
     let updated = active.update(&db).await?;
+    services::history::record_update(&db, &Actor::new(&user.0, "manual"), &existing, &updated)
+        .await
+        .map_err(AppError::Internal)?;
+
     Ok(Json(updated))
 }
+
+pub async fn list_splits(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<transaction_splits::Model>>, AppError> {
+    find(&db, id).await?;
+
+    let splits = services::splits::for_transaction(&db, id)
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(splits))
+}
+
+/// Replace a transaction's splits. They must add up to its amount; from then
+/// on category stats count the splits instead of the transaction's category.
+pub async fn replace_splits(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+    Json(body): Json<SplitsBody>,
+) -> Result<Json<Vec<transaction_splits::Model>>, AppError> {
+    let tx = find(&db, id).await?;
+    services::splits::validate(tx.amount, &body.splits).map_err(AppError::BadRequest)?;
+    for category_id in body.splits.iter().filter_map(|s| s.category_id) {
+        if categories::Entity::find_by_id(category_id)
+            .one(&db)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::BadRequest(format!(
+                "Category {category_id} not found"
+            )));
+        }
+    }
+
+    let splits = services::splits::replace(&db, &tx, body.splits, &Actor::new(&user.0, "manual"))
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(splits))
+}
+
+pub async fn delete_splits(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    find(&db, id).await?;
+
+    services::splits::clear(&db, id, &Actor::new(&user.0, "manual"))
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Every recorded change to a transaction's category, notes, state and
+/// splits, newest first.
+pub async fn history(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<transaction_history::Model>>, AppError> {
+    find(&db, id).await?;
+
+    let entries = services::history::for_transaction(&db, id)
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(entries))
+}
+
+/// Undo the change a history entry belongs to: every field written with it
+/// goes back to its old value. The revert is itself recorded.
+pub async fn revert(
+    State(db): State<DatabaseConnection>,
+    Extension(user): Extension<CurrentUser>,
+    Path((id, entry_id)): Path<(i32, i32)>,
+) -> Result<Json<Transaction>, AppError> {
+    let tx = find(&db, id).await?;
+    let entry = transaction_history::Entity::find_by_id(entry_id)
+        .filter(transaction_history::Column::TransactionId.eq(id))
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("History entry {entry_id} not found")))?;
+    let change = transaction_history::Entity::find()
+        .filter(transaction_history::Column::TransactionId.eq(id))
+        .filter(transaction_history::Column::ChangedAt.eq(entry.changed_at))
+        .all(&db)
+        .await?;
+
+    let restore = services::history::restore(&change).map_err(AppError::BadRequest)?;
+    let categories = restore.category_id.flatten().into_iter().chain(
+        restore
+            .splits
+            .iter()
+            .flatten()
+            .filter_map(|s| s.category_id),
+    );
+    for category_id in categories {
+        if categories::Entity::find_by_id(category_id)
+            .one(&db)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::BadRequest(format!(
+                "Category {category_id} no longer exists"
+            )));
+        }
+    }
+    let amount = restore.amount.unwrap_or(tx.amount);
+    match restore.splits {
+        Some(ref splits) if !splits.is_empty() => {
+            services::splits::validate(amount, splits).map_err(AppError::BadRequest)?;
+        }
+        None if amount != tx.amount => ensure_unsplit(&db, id).await?,
+        _ => {}
+    }
+
+    let updated = services::history::revert(&db, tx, restore, &Actor::new(&user.0, "revert"))
+        .await
+        .map_err(AppError::Internal)?;
+
+    Ok(Json(updated))
+}
+
+/// The transactions a bulk request selects: by `ids`, all of which must
+/// exist outside the trash, or by a non-empty `filter`.
+async fn select(
+    db: &DatabaseConnection,
+    ids: Option<Vec<i32>>,
+    filter: Option<TransactionFilter>,
+) -> Result<Vec<transactions::Model>, AppError> {
+    match (ids, filter) {
+        (Some(ids), None) => {
+            let rows = transactions::Entity::find()
+                .filter(transactions::Column::Id.is_in(ids.iter().copied()))
+                .filter(transactions::Column::DeletedAt.is_null())
+                .order_by_asc(transactions::Column::Id)
+                .all(db)
+                .await?;
+            if let Some(missing) = ids.iter().find(|id| !rows.iter().any(|tx| tx.id == **id)) {
+                return Err(AppError::NotFound(format!(
+                    "Transaction {missing} not found"
+                )));
+            }
+            Ok(rows)
+        }
+        (None, Some(filter)) => {
+            if filter.is_empty() {
+                return Err(AppError::BadRequest(
+                    "An empty filter would match every transaction; pass ids or narrow it".into(),
+                ));
+            }
+            let condition = filter.condition().map_err(AppError::BadRequest)?;
+            Ok(transactions::Entity::find()
+                .join(JoinType::InnerJoin, transactions::Relation::Account.def())
+                .filter(condition)
+                .order_by_desc(transactions::Column::TransactionDate)
+                .order_by_desc(transactions::Column::Id)
+                .all(db)
+                .await?)
+        }
+        _ => Err(AppError::BadRequest("Pass either ids or filter".into())),
+    }
+}
+
+async fn find(db: &DatabaseConnection, id: i32) -> Result<transactions::Model, AppError> {
+    transactions::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {id} not found")))
+}
+
+/// Splits must add up to the amount, so it can't change under them.
+async fn ensure_unsplit(db: &DatabaseConnection, id: i32) -> Result<(), AppError> {
+    let splits = services::splits::for_transaction(db, id)
+        .await
+        .map_err(AppError::Internal)?;
+    if !splits.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Transaction {id} is split; remove its splits before changing the amount"
+        )));
+    }
+    Ok(())
+}
+
+async fn find_account(db: &DatabaseConnection, id: i32) -> Result<accounts::Model, AppError> {
+    accounts::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("Account {id} not found")))
+}
+
+fn valid_description(description: &str) -> Result<String, AppError> {
+    let description = description.trim();
+    if description.is_empty() {
+        return Err(AppError::BadRequest("Description is required".into()));
+    }
+    Ok(description.to_string())
+}
@@ -7,9 +7,13 @@ use axum::{
 };
 use base64::Engine;
 
+/// The signed-in user name, added to each authenticated request's extensions.
+#[derive(Debug, Clone)]
+pub struct CurrentUser(pub String);
+
 pub async fn basic_auth(
     State(config): State<crate::config::Config>,
-    request: Request<axum::body::Body>,
+    mut request: Request<axum::body::Body>,
     next: Next,
 ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let unauthorized = || {
@@ -41,5 +45,7 @@ pub async fn basic_auth(
         return Err(unauthorized());
     }
 
+    let user = CurrentUser(user.to_string());
+    request.extensions_mut().insert(user);
     Ok(next.run(request).await)
 }
@@ -9,6 +9,11 @@ pub struct Model {
     #[sea_orm(unique)]
     pub name: String,
     pub name_pl: Option<String>,
+    /// Categories nest to any depth; `None` for top-level ones.
+    pub parent_id: Option<i32>,
+    /// Archived categories are kept for history but no longer offered or
+    /// assigned automatically.
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
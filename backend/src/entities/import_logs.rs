@@ -12,6 +12,9 @@ pub struct Model {
     pub imported: i32,
     pub skipped: i32,
     pub imported_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[sea_orm(unique)]
+    pub content_sha256: Option<String>,
+    pub archive_path: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -22,6 +25,8 @@ pub enum Relation {
         to = "super::accounts::Column::Id"
     )]
     Account,
+    #[sea_orm(has_many = "super::transactions::Entity")]
+    Transactions,
 }
 
 impl Related<super::accounts::Entity> for Entity {
@@ -30,4 +35,10 @@ impl Related<super::accounts::Entity> for Entity {
     }
 }
 
+impl Related<super::transactions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transactions.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,4 +1,17 @@
 pub mod accounts;
+pub mod attachments;
+pub mod bank_category_mappings;
 pub mod categories;
+pub mod category_overrides;
+pub mod category_rules;
+pub mod category_suggestions;
+pub mod classifier_models;
 pub mod import_logs;
+pub mod import_mappings;
+pub mod jobs;
+pub mod merchant_categories;
+pub mod tags;
+pub mod transaction_history;
+pub mod transaction_splits;
+pub mod transaction_tags;
 pub mod transactions;
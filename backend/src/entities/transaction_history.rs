@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One field of a transaction changed by one edit. Entries written together
+/// share `changed_at`.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "transaction_history")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub transaction_id: i32,
+    /// A transaction column such as `category_id`, `description` or `notes`,
+    /// or `splits`.
+    pub field: String,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub old_value: Option<serde_json::Value>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub new_value: Option<serde_json::Value>,
+    /// The signed-in user, or `system` for background work.
+    pub changed_by: String,
+    /// What made the change: `manual`, `bulk_edit`, `rule`, `ai`, `revert`, ...
+    pub reason: String,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::transactions::Entity",
+        from = "Column::TransactionId",
+        to = "super::transactions::Column::Id"
+    )]
+    Transaction,
+}
+
+impl Related<super::transactions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transaction.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
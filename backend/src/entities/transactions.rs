@@ -25,6 +25,19 @@ pub struct Model {
     #[sea_orm(column_type = "JsonBinary", nullable)]
     pub raw_data: Option<serde_json::Value>,
     pub imported_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub import_log_id: Option<i32>,
+    pub import_row: Option<i32>,
+    pub category_rule_id: Option<i32>,
+    pub notes: Option<String>,
+    /// When `review_status` last moved away from `new`.
+    pub reviewed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// `new` until someone checks it, then `reviewed` or `flagged`.
+    pub review_status: String,
+    /// `import` (statement or JSON ingestion) or `manual` (entered by hand).
+    pub source: String,
+    /// Set while the transaction is in the trash. Trashed rows are left out
+    /// of lists and stats but keep blocking re-import of their hash.
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -41,6 +54,18 @@ pub enum Relation {
         to = "super::categories::Column::Id"
     )]
     Category,
+    #[sea_orm(
+        belongs_to = "super::import_logs::Entity",
+        from = "Column::ImportLogId",
+        to = "super::import_logs::Column::Id"
+    )]
+    ImportLog,
+    #[sea_orm(
+        belongs_to = "super::category_rules::Entity",
+        from = "Column::CategoryRuleId",
+        to = "super::category_rules::Column::Id"
+    )]
+    CategoryRule,
 }
 
 impl Related<super::accounts::Entity> for Entity {
@@ -55,4 +80,16 @@ impl Related<super::categories::Entity> for Entity {
     }
 }
 
+impl Related<super::import_logs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ImportLog.def()
+    }
+}
+
+impl Related<super::category_rules::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CategoryRule.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -10,6 +10,7 @@ mod services;
 
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "finance-tracker", about = "Personal finance tracker")]
@@ -20,13 +21,39 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Import a bank CSV file
+    /// Import bank CSV files
     Import {
-        /// Path to the CSV file
-        file: PathBuf,
+        /// CSV or ZIP files, glob patterns (quoted) or directories
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+    /// Re-run the current parsers over all archived statement files
+    Reparse,
+    /// Train the local category classifier on manually and rule-categorised transactions
+    Train,
+    /// Report how often automatic categories were confirmed or corrected by hand
+    Accuracy {
+        /// Only this category source (ai, rule, bank, classifier)
+        #[arg(long)]
+        source: Option<String>,
     },
     /// Start the HTTP API server
-    Serve,
+    Serve {
+        /// Also watch this directory for new statement files
+        #[arg(long)]
+        watch: Option<PathBuf>,
+        /// Seconds between inbox scans
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+        watch_interval: u64,
+    },
+    /// Watch a directory and import statement files dropped into it
+    Watch {
+        /// Inbox directory; processed files move to done/, failures to failed/
+        dir: PathBuf,
+        /// Seconds between inbox scans
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
 }
 
 #[tokio::main]
@@ -40,39 +67,223 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .init();
 
-    let cfg = config::Config::from_env();
+    let cfg = config::Config::from_env()?;
     let pool = db::create_pool(&cfg.database_url).await?;
     db::run_migrations(&pool).await?;
 
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Import { file } => {
-            let filename = file
-                .file_name()
-                .map(|f| f.to_string_lossy().to_string())
-                .unwrap_or_else(|| "unknown".into());
+        Commands::Import { files } => {
+            let mut uploads = Vec::new();
+            for path in collect_paths(&files)? {
+                let filename = path
+                    .file_name()
+                    .map(|f| f.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "unknown".into());
+
+                let bytes = std::fs::read(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+                tracing::info!("Importing file: {}", path.display());
+                uploads.push((filename, bytes));
+            }
 
-            let content = std::fs::read_to_string(&file)
-                .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+            let results = services::import::import_batch(&pool, &cfg.archive_dir, uploads).await;
 
-            tracing::info!("Importing file: {}", file.display());
+            let mut failed = 0;
+            for file in &results {
+                match &file.result {
+                    Ok(r) => println!(
+                        "{}: {} total, {} imported, {} skipped (duplicates)",
+                        file.filename, r.total_rows, r.imported, r.skipped
+                    ),
+                    Err(e) => {
+                        failed += 1;
+                        println!("{}: FAILED: {}", file.filename, e);
+                    }
+                }
+            }
+
+            let ok = results.iter().filter_map(|f| f.result.as_ref().ok());
+            let (total, imported, skipped) = ok.fold((0, 0, 0), |acc, r| {
+                (acc.0 + r.total_rows, acc.1 + r.imported, acc.2 + r.skipped)
+            });
+            println!(
+                "Import complete: {} files ({} failed), {} total, {} imported, {} skipped (duplicates)",
+                results.len(),
+                failed,
+                total,
+                imported,
+                skipped
+            );
+
+            if failed == results.len() {
+                return Err("No file could be imported".into());
+            }
+        }
+        Commands::Reparse => {
+            let result = services::import::reparse_archived(
+                &pool,
+                &cfg.archive_dir,
+                &services::history::Actor::system("reparse"),
+            )
+            .await?;
 
-            let result = services::import::import_file(&pool, &filename, &content).await?;
+            println!(
+                "Reparse complete: {} files ({} failed), {} updated ({} with splits cleared), {} unchanged",
+                result.files,
+                result.failed_files,
+                result.updated,
+                result.splits_cleared,
+                result.unchanged
+            );
+        }
+        Commands::Train => {
+            let result = services::classifier::train(&pool).await?;
 
+            let accuracy = result
+                .accuracy
+                .map(|a| format!("{:.1}%", a * 100.0))
+                .unwrap_or_else(|| "n/a (too few transactions)".into());
             println!(
-                "Import complete: {} total, {} imported, {} skipped (duplicates)",
-                result.total_rows, result.imported, result.skipped
+                "Trained classifier #{}: {} transactions, {} categories, cross-validated accuracy {}",
+                result.model_id, result.samples, result.classes, accuracy
             );
         }
-        Commands::Serve => {
-            let router = api::create_router(pool, cfg.clone());
+        Commands::Accuracy { source } => {
+            let report = services::accuracy::report(&pool, source.as_deref()).await?;
+            print_accuracy(&report);
+        }
+        Commands::Serve {
+            watch,
+            watch_interval,
+        } => {
+            if let Some(dir) = watch {
+                let (db, archive_dir) = (pool.clone(), cfg.archive_dir.clone());
+                let interval = Duration::from_secs(watch_interval);
+                tokio::spawn(async move {
+                    if let Err(e) = services::watch::watch(db, archive_dir, dir, interval).await {
+                        tracing::error!("Inbox watcher stopped: {}", e);
+                    }
+                });
+            }
+
+            let interrupted = services::jobs::fail_interrupted(&pool).await?;
+            if interrupted > 0 {
+                tracing::warn!("Marked {} interrupted job(s) as failed", interrupted);
+            }
+
+            let attachments = services::attachments::Store::open(&cfg.attachments)?;
+            let router = api::create_router(pool, cfg.clone(), attachments);
 
             let listener = tokio::net::TcpListener::bind(&cfg.bind_addr).await?;
             tracing::info!("Listening on {}", cfg.bind_addr);
             axum::serve(listener, router).await?;
         }
+        Commands::Watch { dir, interval } => {
+            services::watch::watch(
+                pool,
+                cfg.archive_dir.clone(),
+                dir,
+                Duration::from_secs(interval),
+            )
+            .await?;
+        }
     }
 
     Ok(())
 }
+
+fn print_accuracy(report: &services::accuracy::Report) {
+    let percent = |v: Option<f64>| {
+        v.map(|v| format!("{:.1}%", v * 100.0))
+            .unwrap_or_else(|| "-".into())
+    };
+
+    if report.sources.is_empty() {
+        println!("No automatically categorised transactions yet");
+    }
+
+    for source in &report.sources {
+        println!(
+            "\n{}: {} evaluated, {} corrected, accuracy {}",
+            source.source,
+            source.evaluated,
+            source.corrected,
+            percent(source.accuracy)
+        );
+        println!(
+            "  {:<24} {:>9} {:>9} {:>9} {:>9}",
+            "category", "predicted", "actual", "precision", "recall"
+        );
+        for c in &source.categories {
+            println!(
+                "  {:<24} {:>9} {:>9} {:>9} {:>9}",
+                c.category,
+                c.predicted,
+                c.actual,
+                percent(c.precision),
+                percent(c.recall)
+            );
+        }
+
+        let mistakes: Vec<_> = source
+            .confusion
+            .iter()
+            .filter(|cell| cell.predicted_id != cell.actual_id)
+            .collect();
+        if !mistakes.is_empty() {
+            println!("  confusions (predicted -> actual):");
+            for cell in mistakes {
+                println!("    {} -> {}: {}", cell.predicted, cell.actual, cell.count);
+            }
+        }
+    }
+
+    if !report.corrected_merchants.is_empty() {
+        println!("\nMost corrected merchants:");
+        for m in &report.corrected_merchants {
+            println!(
+                "  {:<32} {:>4} ({})",
+                m.merchant,
+                m.corrections,
+                m.sources.join(", ")
+            );
+        }
+    }
+}
+
+/// Expand CLI arguments into file paths: glob patterns are matched, directories
+/// contribute the files directly inside them (sorted), plain paths pass through.
+fn collect_paths(args: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut paths = Vec::new();
+
+    for arg in args {
+        if arg.contains(['*', '?', '[']) {
+            let matches = glob::glob(arg).map_err(|e| format!("Invalid pattern '{arg}': {e}"))?;
+            let before = paths.len();
+            for entry in matches {
+                let path = entry.map_err(|e| format!("Failed to read {arg}: {e}"))?;
+                if path.is_file() {
+                    paths.push(path);
+                }
+            }
+            if paths.len() == before {
+                return Err(format!("No files match '{arg}'"));
+            }
+        } else if std::path::Path::new(arg).is_dir() {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(arg)
+                .map_err(|e| format!("Failed to read directory {arg}: {e}"))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.is_file())
+                .collect();
+            entries.sort();
+            paths.extend(entries);
+        } else {
+            paths.push(PathBuf::from(arg));
+        }
+    }
+
+    Ok(paths)
+}
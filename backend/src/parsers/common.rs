@@ -1,5 +1,7 @@
+use chrono::NaiveDate;
 use rust_decimal::Decimal;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::str::FromStr;
 
 /// Parse Polish decimal format: "1 234,56" or "-1234,56" → Decimal
@@ -13,6 +15,142 @@ pub fn parse_polish_decimal(s: &str) -> Result<Decimal, String> {
     Decimal::from_str(&cleaned).map_err(|e| format!("Invalid decimal '{}': {}", s, e))
 }
 
+/// Parse an amount whose decimal separator is known from the format:
+/// "$1,234.56", "-12.340000000000", "0.500", "(45.00)" with `.`, or
+/// "1 234,56 zł" with `,` → Decimal. The other separator only groups
+/// thousands.
+pub fn parse_amount(s: &str, decimal: char) -> Result<Decimal, String> {
+    let (negative, kept) = amount_digits(s)?;
+    let grouping = if decimal == '.' { ',' } else { '.' };
+    let kept = kept.replace(grouping, "");
+    if kept.matches(decimal).count() > 1 {
+        return Err(format!("Invalid amount '{}'", s));
+    }
+
+    let value = Decimal::from_str(&kept.replace(decimal, "."))
+        .map_err(|e| format!("Invalid amount '{}': {}", s, e))?;
+    Ok(if negative { -value } else { value })
+}
+
+/// Decide the decimal separator of a file whose amounts all share one
+/// currency format with two decimal places: `,` as soon as any amount ends in
+/// a comma and two digits ("49,47zł"), `.` otherwise.
+pub fn decimal_separator<'a>(amounts: impl IntoIterator<Item = &'a str>) -> char {
+    let comma = amounts.into_iter().any(|a| {
+        let kept: String = a
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+            .collect();
+        kept.rfind(['.', ','])
+            .is_some_and(|pos| kept[pos..].starts_with(',') && kept.len() - pos == 3)
+    });
+    if comma {
+        ','
+    } else {
+        '.'
+    }
+}
+
+/// Parse an amount exported by an app whose format follows the user's
+/// locale: "$1,234.56", "1 234,56 zł", "1.234,5", "(45.00)" → Decimal.
+///
+/// With both `.` and `,` present the last one is the decimal separator. With
+/// only one kind, it is a thousands separator when repeated or when followed
+/// by exactly three digits ("1,234"), and the decimal separator otherwise.
+pub fn parse_any_amount(s: &str) -> Result<Decimal, String> {
+    let (negative, kept) = amount_digits(s)?;
+
+    let normalized = match kept.rfind(['.', ',']) {
+        Some(pos) => {
+            let separator = kept.as_bytes()[pos] as char;
+            let (int_part, frac_part) = (&kept[..pos], &kept[pos + 1..]);
+            let mixed = int_part.contains(if separator == '.' { ',' } else { '.' });
+            let repeated = int_part.contains(separator);
+            let is_decimal = mixed || (!repeated && frac_part.len() != 3);
+            if is_decimal {
+                format!("{}.{}", int_part.replace(['.', ','], ""), frac_part)
+            } else {
+                kept.replace(['.', ','], "")
+            }
+        }
+        None => kept,
+    };
+
+    let value =
+        Decimal::from_str(&normalized).map_err(|e| format!("Invalid amount '{}': {}", s, e))?;
+    Ok(if negative { -value } else { value })
+}
+
+/// Whether an amount is negative ("-" or accounting parentheses), and its
+/// digits and separators with currency symbols and spaces dropped.
+fn amount_digits(s: &str) -> Result<(bool, String), String> {
+    let trimmed = s.trim();
+    let negative = trimmed.starts_with('-') || (trimmed.starts_with('(') && trimmed.ends_with(')'));
+    let kept: String = trimmed
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+        .collect();
+
+    if !kept.chars().any(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid amount '{}'", s));
+    }
+    Ok((negative, kept))
+}
+
+/// Parse a date in any of the layouts other apps export: ISO (optionally with a
+/// time part), "DD.MM.YYYY", or slash-separated with the day first or the
+/// month first depending on `day_first`.
+pub fn parse_any_date(s: &str, day_first: bool) -> Result<NaiveDate, String> {
+    let s = s.trim();
+    let date_part = s.split(['T', ' ']).next().unwrap_or(s);
+
+    let formats: &[&str] = if day_first {
+        &["%Y-%m-%d", "%d/%m/%Y", "%d.%m.%Y", "%d-%m-%Y", "%Y/%m/%d"]
+    } else {
+        &["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y", "%m-%d-%Y", "%Y/%m/%d"]
+    };
+
+    formats
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(date_part, f).ok())
+        .ok_or_else(|| format!("unrecognised date '{}'", s))
+}
+
+/// Decide whether slash-separated dates in a file are day-first: true as soon
+/// as any leading component is above 12. US-style month-first otherwise.
+pub fn dates_are_day_first<'a>(dates: impl IntoIterator<Item = &'a str>) -> bool {
+    dates.into_iter().any(|d| {
+        d.trim()
+            .split(['/', '-', '.'])
+            .next()
+            .and_then(|first| first.parse::<u32>().ok())
+            .is_some_and(|first| first > 12 && first <= 31)
+    })
+}
+
+/// Map lower-cased header names to column indices, for exports whose column
+/// order is configurable.
+pub fn header_index(headers: &csv::StringRecord) -> HashMap<String, usize> {
+    headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (h.trim().trim_start_matches('\u{feff}').to_lowercase(), i))
+        .collect()
+}
+
+/// Trimmed value of the named column, or "" if the column or value is missing.
+pub fn field<'r>(
+    record: &'r csv::StringRecord,
+    index: &HashMap<String, usize>,
+    name: &str,
+) -> &'r str {
+    index
+        .get(name)
+        .and_then(|&i| record.get(i))
+        .unwrap_or("")
+        .trim()
+}
+
 /// Fix mojibake from double-encoded UTF-8 (UTF-8 → Latin-1 → UTF-8).
 /// Revolut exports suffer from this: "ę" (c4 99) becomes "Ä™" (c3 84 c2 99).
 pub fn fix_mojibake(s: &str) -> String {
@@ -91,6 +229,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_amount() {
+        let d = |s: &str| Decimal::from_str(s).unwrap();
+        assert_eq!(parse_amount("$1,234.56", '.').unwrap(), d("1234.56"));
+        assert_eq!(parse_amount("-12.340000000000", '.').unwrap(), d("-12.34"));
+        assert_eq!(parse_amount("12.340", '.').unwrap(), d("12.34"));
+        assert_eq!(parse_amount("0.500", '.').unwrap(), d("0.5"));
+        assert_eq!(parse_amount("(45.00)", '.').unwrap(), d("-45.00"));
+        assert_eq!(parse_amount("1,234", '.').unwrap(), d("1234"));
+        assert_eq!(parse_amount("7", '.').unwrap(), d("7"));
+        assert_eq!(parse_amount("1 234,56 zł", ',').unwrap(), d("1234.56"));
+        assert_eq!(parse_amount("1.234,5", ',').unwrap(), d("1234.5"));
+        assert!(parse_amount("1.234.5", '.').is_err());
+        assert!(parse_amount("n/a", '.').is_err());
+
+        assert_eq!(decimal_separator(["0,00zł", "49,47zł"]), ',');
+        assert_eq!(decimal_separator(["$0.00", "$1,234.56"]), '.');
+        assert_eq!(decimal_separator(["1,234"]), '.');
+    }
+
+    #[test]
+    fn test_parse_any_amount() {
+        let d = |s: &str| Decimal::from_str(s).unwrap();
+        assert_eq!(parse_any_amount("$1,234.56").unwrap(), d("1234.56"));
+        assert_eq!(parse_any_amount("1 234,56 zł").unwrap(), d("1234.56"));
+        assert_eq!(parse_any_amount("-12.340000000000").unwrap(), d("-12.34"));
+        assert_eq!(parse_any_amount("(45.00)").unwrap(), d("-45.00"));
+        assert_eq!(parse_any_amount("1.234,5").unwrap(), d("1234.5"));
+        assert_eq!(parse_any_amount("1,234").unwrap(), d("1234"));
+        assert_eq!(parse_any_amount("0,50").unwrap(), d("0.50"));
+        assert!(parse_any_amount("n/a").is_err());
+    }
+
+    #[test]
+    fn test_parse_any_date() {
+        let feb = NaiveDate::from_ymd_opt(2026, 2, 3).unwrap();
+        assert_eq!(
+            parse_any_date("2026-02-03T00:00:00+01:00", false).unwrap(),
+            feb
+        );
+        assert_eq!(parse_any_date("02/03/2026", false).unwrap(), feb);
+        assert_eq!(parse_any_date("03/02/2026", true).unwrap(), feb);
+        assert_eq!(parse_any_date("03.02.2026", false).unwrap(), feb);
+        assert!(parse_any_date("yesterday", true).is_err());
+
+        assert!(dates_are_day_first(["01/02/2026", "23/02/2026"]));
+        assert!(!dates_are_day_first(["01/02/2026", "02/23/2026"]));
+    }
+
     #[test]
     fn test_compute_hash_deterministic() {
         let h1 = compute_hash("alior", "2026-02-23", "-1180.00", "Za treningi");
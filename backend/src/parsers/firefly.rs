@@ -0,0 +1,196 @@
+use super::{common, ParsedTransaction};
+use serde_json::json;
+
+/// Source name recorded in `raw_data` and used for the account/category mappings.
+pub const SOURCE: &str = "firefly";
+
+/// `bank_category` of uncategorised transfers, so the category mappings file
+/// them under our own Transfers category rather than as spending.
+const TRANSFER_CATEGORY: &str = "Transfers";
+
+/// Parse a Firefly III CSV export ("Export data" → transactions).
+///
+/// Format:
+/// - Line 1: Headers (comma-delimited), looked up by name
+/// - `amount` is unsigned, with `.` as the decimal separator; `type` (Withdrawal / Deposit / Transfer) gives the
+///   direction, and the own account is `source_name` for withdrawals and
+///   transfers, `destination_name` for deposits
+/// - Dates: ISO 8601 with time and offset
+/// - A transfer to an asset account becomes two rows: outgoing on the source
+///   account and incoming on the destination account
+///
+/// `account` is the Firefly account name and `bank_category` the Firefly
+/// category; both are resolved through the import mappings afterwards.
+pub fn parse(content: &str) -> Result<Vec<ParsedTransaction>, String> {
+    let cleaned = content.replace('\r', "");
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b',')
+        .has_headers(true)
+        .flexible(true)
+        .trim(csv::Trim::Fields)
+        .from_reader(cleaned.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("CSV header error: {}", e))?
+        .clone();
+    let index = common::header_index(&headers);
+
+    let mut transactions = Vec::new();
+
+    for (row_idx, result) in reader.records().enumerate() {
+        let record =
+            result.map_err(|e| format!("CSV parse error at row {}: {}", row_idx + 2, e))?;
+        let get = |name: &str| common::field(&record, &index, name);
+
+        let date_str = get("date");
+        if date_str.is_empty() {
+            continue;
+        }
+        let transaction_date = common::parse_any_date(date_str, true)
+            .map_err(|e| format!("Row {}: {}", row_idx + 2, e))?;
+
+        let amount_str = get("amount");
+        let magnitude = common::parse_amount(amount_str, '.')
+            .map_err(|e| format!("Row {}: {}", row_idx + 2, e))?
+            .abs()
+            .round_dp(2);
+
+        let kind = get("type");
+        let transfer = kind.eq_ignore_ascii_case("transfer");
+        let mut sides = vec![if kind.eq_ignore_ascii_case("deposit") {
+            (get("destination_name"), get("source_name"), magnitude)
+        } else {
+            (get("source_name"), get("destination_name"), -magnitude)
+        }];
+        if transfer && get("destination_type").eq_ignore_ascii_case("asset account") {
+            sides.push((get("destination_name"), get("source_name"), magnitude));
+        }
+
+        if sides[0].0.is_empty() {
+            tracing::warn!("Firefly row {} has no own account, skipping", row_idx + 2);
+            continue;
+        }
+
+        let description = common::normalize_whitespace(get("description"));
+        let category = match get("category") {
+            "" if transfer => TRANSFER_CATEGORY,
+            category => category,
+        };
+
+        let raw_data = json!({
+            "source": SOURCE,
+            "source_id": get("journal_id"),
+            "group_id": get("group_id"),
+            "group_title": get("group_title"),
+            "type": kind,
+            "amount": amount_str,
+            "date": date_str,
+            "source_name": get("source_name"),
+            "source_iban": get("source_iban"),
+            "destination_name": get("destination_name"),
+            "destination_iban": get("destination_iban"),
+            "category": get("category"),
+            "budget": get("budget"),
+            "bill": get("bill"),
+            "tags": get("tags"),
+            "notes": get("notes"),
+        });
+
+        for (account, counterparty, amount) in sides.into_iter().filter(|s| !s.0.is_empty()) {
+            transactions.push(ParsedTransaction {
+                account: account.to_string(),
+                transaction_date,
+                booking_date: None,
+                counterparty: if counterparty.is_empty() {
+                    None
+                } else {
+                    Some(common::normalize_whitespace(counterparty))
+                },
+                description: description.clone(),
+                amount,
+                currency: match get("currency_code") {
+                    "" => "PLN".to_string(),
+                    code => code.to_string(),
+                },
+                bank_category: if category.is_empty() {
+                    None
+                } else {
+                    Some(category.to_string())
+                },
+                bank_reference: None,
+                bank_type: if kind.is_empty() {
+                    None
+                } else {
+                    Some(kind.to_string())
+                },
+                state: "completed".to_string(),
+                raw_data: raw_data.clone(),
+            });
+        }
+    }
+
+    if transactions.is_empty() {
+        return Err("No valid transactions found in Firefly III CSV".into());
+    }
+
+    tracing::info!("Parsed {} Firefly III transactions", transactions.len());
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn sample_csv() -> &'static str {
+        "user_id,group_id,journal_id,created_at,updated_at,group_title,type,amount,foreign_amount,currency_code,foreign_currency_code,description,date,source_name,source_iban,source_type,destination_name,destination_iban,destination_type,reconciled,category,budget,bill,tags,notes\n\
+         1,10,11,2026-02-01T10:00:00+01:00,2026-02-01T10:00:00+01:00,,Withdrawal,49.470000000000,,PLN,,Zakupy spożywcze,2026-02-21T00:00:00+01:00,Konto główne,PL61109010140000071219812874,Asset account,Biedronka,,Expense account,false,Groceries,Food,,,\n\
+         1,12,13,2026-02-01T10:00:00+01:00,2026-02-01T10:00:00+01:00,,Deposit,5000.000000000000,,PLN,,Wynagrodzenie,2026-02-22T00:00:00+01:00,ACME Sp. z o.o.,,Revenue account,Konto główne,,Asset account,false,Salary,,,,\n\
+         1,14,15,2026-02-01T10:00:00+01:00,2026-02-01T10:00:00+01:00,,Transfer,1000.000000000000,,PLN,,Oszczędności,2026-02-23T00:00:00+01:00,Konto główne,,Asset account,Konto oszczędnościowe,,Asset account,false,,,,,\n"
+    }
+
+    #[test]
+    fn test_parse_firefly_basic() {
+        let result = parse(sample_csv()).expect("should parse successfully");
+        assert_eq!(result.len(), 4);
+
+        assert_eq!(result[0].account, "Konto główne");
+        assert_eq!(
+            result[0].transaction_date,
+            NaiveDate::from_ymd_opt(2026, 2, 21).unwrap()
+        );
+        assert_eq!(result[0].amount, Decimal::from_str("-49.47").unwrap());
+        assert_eq!(result[0].counterparty.as_deref(), Some("Biedronka"));
+        assert_eq!(result[0].description, "Zakupy spożywcze");
+        assert_eq!(result[0].bank_category.as_deref(), Some("Groceries"));
+        assert_eq!(result[0].raw_data["source_id"], "11");
+        assert_eq!(result[0].raw_data["source"], SOURCE);
+
+        assert_eq!(result[1].account, "Konto główne");
+        assert_eq!(result[1].amount, Decimal::from_str("5000").unwrap());
+        assert_eq!(result[1].counterparty.as_deref(), Some("ACME Sp. z o.o."));
+        assert_eq!(result[1].bank_type.as_deref(), Some("Deposit"));
+
+        assert_eq!(result[2].account, "Konto główne");
+        assert_eq!(result[2].amount, Decimal::from_str("-1000").unwrap());
+        assert_eq!(result[2].bank_category.as_deref(), Some(TRANSFER_CATEGORY));
+        assert_eq!(result[2].raw_data["category"], "");
+
+        // The incoming side of the transfer lands on the destination account
+        assert_eq!(result[3].account, "Konto oszczędnościowe");
+        assert_eq!(result[3].amount, Decimal::from_str("1000").unwrap());
+        assert_eq!(result[3].counterparty.as_deref(), Some("Konto główne"));
+        assert_eq!(result[3].bank_category.as_deref(), Some(TRANSFER_CATEGORY));
+        assert_eq!(result[3].raw_data["source_id"], "15");
+    }
+
+    #[test]
+    fn test_parse_firefly_empty() {
+        let csv = "user_id,group_id,journal_id,type,amount,description,date,source_name,destination_name,category\n";
+        assert!(parse(csv).is_err());
+    }
+}
@@ -0,0 +1,193 @@
+use super::{common, ParsedTransaction};
+use serde_json::json;
+
+/// Source name recorded in `raw_data` and used for the account/category mappings.
+pub const SOURCE: &str = "mmex";
+
+/// Parse a Money Manager Ex CSV export.
+///
+/// Format:
+/// - Line 1: Headers, comma- or semicolon-delimited depending on the export
+///   settings, looked up by name: ID, Date, Status, Type, Account, Payee,
+///   Category, SubCategory (older versions), Amount, Currency, Number, Notes
+/// - `Type` is Withdrawal / Deposit / Transfer; withdrawals and transfers are
+///   made negative even when exported unsigned
+/// - Amounts: in the user's locale, so the decimal separator is inferred
+///   from each value
+/// - Dates: in the user's date format, day-first vs month-first decided once
+///   for the whole file
+pub fn parse(content: &str) -> Result<Vec<ParsedTransaction>, String> {
+    let cleaned = content.replace('\r', "");
+
+    let header_line = cleaned.lines().next().unwrap_or("");
+    let delimiter = if header_line.matches(';').count() > header_line.matches(',').count() {
+        b';'
+    } else {
+        b','
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(true)
+        .flexible(true)
+        .trim(csv::Trim::Fields)
+        .from_reader(cleaned.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("CSV header error: {}", e))?
+        .clone();
+    let index = common::header_index(&headers);
+
+    let records = reader
+        .records()
+        .enumerate()
+        .map(|(row_idx, r)| r.map_err(|e| format!("CSV parse error at row {}: {}", row_idx + 2, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let day_first =
+        common::dates_are_day_first(records.iter().map(|r| common::field(r, &index, "date")));
+
+    let mut transactions = Vec::new();
+
+    for (row_idx, record) in records.iter().enumerate() {
+        let get = |name: &str| common::field(record, &index, name);
+
+        let date_str = get("date");
+        if date_str.is_empty() {
+            continue;
+        }
+        let transaction_date = common::parse_any_date(date_str, day_first)
+            .map_err(|e| format!("Row {}: {}", row_idx + 2, e))?;
+
+        let amount_str = get("amount");
+        let amount = common::parse_any_amount(amount_str)
+            .map_err(|e| format!("Row {}: {}", row_idx + 2, e))?;
+        let kind = get("type");
+        let amount = if kind.eq_ignore_ascii_case("deposit") {
+            amount.abs()
+        } else if kind.is_empty() {
+            amount
+        } else {
+            -amount.abs()
+        };
+
+        let account = get("account");
+        if account.is_empty() {
+            tracing::warn!(
+                "Money Manager Ex row {} has no account, skipping",
+                row_idx + 2
+            );
+            continue;
+        }
+
+        let payee = common::normalize_whitespace(get("payee"));
+        let notes = common::normalize_whitespace(get("notes"));
+
+        let category = match (get("category"), get("subcategory")) {
+            (cat, "") => cat.to_string(),
+            (cat, sub) => format!("{cat}:{sub}"),
+        };
+
+        let raw_data = json!({
+            "source": SOURCE,
+            "source_id": get("id"),
+            "date": date_str,
+            "status": get("status"),
+            "type": kind,
+            "account": account,
+            "payee": &payee,
+            "category": &category,
+            "amount": amount_str,
+            "currency": get("currency"),
+            "number": get("number"),
+            "notes": &notes,
+        });
+
+        transactions.push(ParsedTransaction {
+            account: account.to_string(),
+            transaction_date,
+            booking_date: None,
+            counterparty: if payee.is_empty() {
+                None
+            } else {
+                Some(payee.clone())
+            },
+            description: if notes.is_empty() { payee } else { notes },
+            amount,
+            currency: match get("currency") {
+                "" => "PLN".to_string(),
+                code => code.to_string(),
+            },
+            bank_category: if category.is_empty() {
+                None
+            } else {
+                Some(category)
+            },
+            bank_reference: match get("number") {
+                "" => None,
+                number => Some(number.to_string()),
+            },
+            bank_type: if kind.is_empty() {
+                None
+            } else {
+                Some(kind.to_string())
+            },
+            state: match get("status") {
+                "V" | "Void" => "reversed".to_string(),
+                _ => "completed".to_string(),
+            },
+            raw_data,
+        });
+    }
+
+    if transactions.is_empty() {
+        return Err("No valid transactions found in Money Manager Ex CSV".into());
+    }
+
+    tracing::info!(
+        "Parsed {} Money Manager Ex transactions",
+        transactions.len()
+    );
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn sample_csv() -> &'static str {
+        "ID;Date;Status;Type;Account;Payee;Category;SubCategory;Amount;Currency;Number;Notes\n\
+         101;21.02.2026;R;Withdrawal;Portfel;Biedronka;Food;Groceries;49,47;PLN;;\n\
+         102;22.02.2026;R;Deposit;Konto;ACME;Income;Salary;5000,00;PLN;FV/1;Pensja\n"
+    }
+
+    #[test]
+    fn test_parse_mmex_basic() {
+        let result = parse(sample_csv()).expect("should parse successfully");
+        assert_eq!(result.len(), 2);
+
+        assert_eq!(result[0].account, "Portfel");
+        assert_eq!(
+            result[0].transaction_date,
+            NaiveDate::from_ymd_opt(2026, 2, 21).unwrap()
+        );
+        assert_eq!(result[0].amount, Decimal::from_str("-49.47").unwrap());
+        assert_eq!(result[0].bank_category.as_deref(), Some("Food:Groceries"));
+        assert_eq!(result[0].description, "Biedronka");
+        assert_eq!(result[0].raw_data["source_id"], "101");
+
+        assert_eq!(result[1].amount, Decimal::from_str("5000.00").unwrap());
+        assert_eq!(result[1].description, "Pensja");
+        assert_eq!(result[1].bank_reference.as_deref(), Some("FV/1"));
+    }
+
+    #[test]
+    fn test_parse_mmex_empty() {
+        let csv = "ID,Date,Status,Type,Account,Payee,Category,Amount,Currency,Number,Notes\n";
+        assert!(parse(csv).is_err());
+    }
+}
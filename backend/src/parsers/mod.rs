@@ -1,7 +1,10 @@
 pub mod alior;
 pub mod common;
+pub mod firefly;
+pub mod mmex;
 pub mod pekao;
 pub mod revolut;
+pub mod ynab;
 
 use chrono::NaiveDate;
 use rust_decimal::Decimal;
@@ -20,28 +23,69 @@ pub struct ParsedTransaction {
     pub bank_category: Option<String>,
     pub bank_reference: Option<String>,
     pub bank_type: Option<String>,
+    #[serde(default = "default_state")]
     pub state: String,
+    #[serde(default)]
     pub raw_data: serde_json::Value,
 }
 
-/// Detect bank format from file content and parse accordingly
-pub fn detect_and_parse(filename: &str, content: &str) -> Result<Vec<ParsedTransaction>, String> {
+fn default_state() -> String {
+    "completed".to_string()
+}
+
+/// A supported statement or export format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Alior,
+    Pekao,
+    Revolut,
+    Firefly,
+    Ynab,
+    MoneyManager,
+}
+
+impl Format {
+    pub fn parse(self, content: &str) -> Result<Vec<ParsedTransaction>, String> {
+        match self {
+            Self::Alior => alior::parse(content),
+            Self::Pekao => pekao::parse(content),
+            Self::Revolut => revolut::parse(content),
+            Self::Firefly => firefly::parse(content),
+            Self::Ynab => ynab::parse(content),
+            Self::MoneyManager => mmex::parse(content),
+        }
+    }
+
+    /// Source name of exports from other finance apps. Their accounts and
+    /// categories are the other app's names and go through the import mappings.
+    pub fn migration_source(self) -> Option<&'static str> {
+        match self {
+            Self::Firefly => Some(firefly::SOURCE),
+            Self::Ynab => Some(ynab::SOURCE),
+            Self::MoneyManager => Some(mmex::SOURCE),
+            Self::Alior | Self::Pekao | Self::Revolut => None,
+        }
+    }
+}
+
+/// Detect the file format from filename patterns, falling back to content headers
+pub fn detect(filename: &str, content: &str) -> Result<Format, String> {
     // Try to detect based on filename patterns
     let lower = filename.to_lowercase();
 
     if lower.contains("historia_operacji") {
         tracing::info!("Detected Alior format from filename");
-        return alior::parse(content);
+        return Ok(Format::Alior);
     }
 
     if lower.contains("lista_operacji") {
         tracing::info!("Detected Pekao format from filename");
-        return pekao::parse(content);
+        return Ok(Format::Pekao);
     }
 
     if lower.contains("account-statement") || lower.contains("revolut") {
         tracing::info!("Detected Revolut format from filename");
-        return revolut::parse(content);
+        return Ok(Format::Revolut);
     }
 
     // Fallback: detect from content
@@ -49,7 +93,7 @@ pub fn detect_and_parse(filename: &str, content: &str) -> Result<Vec<ParsedTrans
 
     if first_lines.contains("Kryteria transakcji") {
         tracing::info!("Detected Alior format from content");
-        return alior::parse(content);
+        return Ok(Format::Alior);
     }
 
     if first_lines.contains("Data ksiegowania")
@@ -57,13 +101,33 @@ pub fn detect_and_parse(filename: &str, content: &str) -> Result<Vec<ParsedTrans
         || first_lines.contains("Kategoria")
     {
         tracing::info!("Detected Pekao format from content");
-        return pekao::parse(content);
+        return Ok(Format::Pekao);
     }
 
     if first_lines.contains("Rodzaj") && first_lines.contains("Produkt") {
         tracing::info!("Detected Revolut format from content");
-        return revolut::parse(content);
+        return Ok(Format::Revolut);
+    }
+
+    // Exports from other finance apps, recognised by their header row
+    let header = content.lines().next().unwrap_or("").to_lowercase();
+
+    if header.contains("journal_id") && header.contains("source_name") {
+        tracing::info!("Detected Firefly III format from content");
+        return Ok(Format::Firefly);
+    }
+
+    if header.contains("category group/category")
+        || (header.contains("outflow") && header.contains("inflow"))
+    {
+        tracing::info!("Detected YNAB format from content");
+        return Ok(Format::Ynab);
+    }
+
+    if header.contains("payee") && header.contains("account") && header.contains("amount") {
+        tracing::info!("Detected Money Manager Ex format from content");
+        return Ok(Format::MoneyManager);
     }
 
-    Err("Unable to detect CSV format. Supported: Alior (Historia_Operacji_*), Pekao (Lista_operacji_*), Revolut (account-statement_*)".into())
+    Err("Unable to detect CSV format. Supported: Alior (Historia_Operacji_*), Pekao (Lista_operacji_*), Revolut (account-statement_*), Firefly III, YNAB and Money Manager Ex exports".into())
 }
@@ -0,0 +1,190 @@
+use super::{common, ParsedTransaction};
+use serde_json::json;
+
+/// Source name recorded in `raw_data` and used for the account/category mappings.
+pub const SOURCE: &str = "ynab";
+
+/// Parse a YNAB register CSV export.
+///
+/// Format:
+/// - Line 1: Headers (comma-delimited, possibly with a BOM), looked up by name:
+///   Account, Flag, Date, Payee, Category Group/Category, Category Group,
+///   Category, Memo, Outflow, Inflow, Cleared
+/// - Amounts: separate Outflow / Inflow columns in the budget's currency
+///   format ("$1,234.56", "1 234,56zł"); the decimal separator is decided
+///   once for the whole file
+/// - Dates: in the budget's date format; day-first vs month-first is decided
+///   once for the whole file
+///
+/// The register carries no transaction ids, so the full row is kept in
+/// `raw_data` instead.
+pub fn parse(content: &str) -> Result<Vec<ParsedTransaction>, String> {
+    let cleaned = content.replace('\r', "");
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b',')
+        .has_headers(true)
+        .flexible(true)
+        .trim(csv::Trim::Fields)
+        .from_reader(cleaned.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("CSV header error: {}", e))?
+        .clone();
+    let index = common::header_index(&headers);
+
+    let records = reader
+        .records()
+        .enumerate()
+        .map(|(row_idx, r)| r.map_err(|e| format!("CSV parse error at row {}: {}", row_idx + 2, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let day_first =
+        common::dates_are_day_first(records.iter().map(|r| common::field(r, &index, "date")));
+
+    let decimal = common::decimal_separator(records.iter().flat_map(|r| {
+        [
+            common::field(r, &index, "outflow"),
+            common::field(r, &index, "inflow"),
+        ]
+    }));
+
+    let mut transactions = Vec::new();
+
+    for (row_idx, record) in records.iter().enumerate() {
+        let get = |name: &str| common::field(record, &index, name);
+
+        let date_str = get("date");
+        if date_str.is_empty() {
+            continue;
+        }
+        let transaction_date = common::parse_any_date(date_str, day_first)
+            .map_err(|e| format!("Row {}: {}", row_idx + 2, e))?;
+
+        let outflow = parse_optional_amount(get("outflow"), decimal)
+            .map_err(|e| format!("Row {}: {}", row_idx + 2, e))?;
+        let inflow = parse_optional_amount(get("inflow"), decimal)
+            .map_err(|e| format!("Row {}: {}", row_idx + 2, e))?;
+        let amount = inflow.abs() - outflow.abs();
+
+        let account = get("account");
+        if account.is_empty() {
+            tracing::warn!("YNAB row {} has no account, skipping", row_idx + 2);
+            continue;
+        }
+
+        let payee = common::normalize_whitespace(get("payee"));
+        let memo = common::normalize_whitespace(get("memo"));
+
+        // "Category Group/Category" is unique across groups; fall back to the
+        // bare category for exports that lack the combined column
+        let category = match get("category group/category") {
+            "" => get("category"),
+            combined => combined,
+        };
+
+        let raw_data = json!({
+            "source": SOURCE,
+            "account": account,
+            "flag": get("flag"),
+            "date": date_str,
+            "payee": &payee,
+            "category_group": get("category group"),
+            "category": get("category"),
+            "memo": &memo,
+            "outflow": get("outflow"),
+            "inflow": get("inflow"),
+            "cleared": get("cleared"),
+        });
+
+        transactions.push(ParsedTransaction {
+            account: account.to_string(),
+            transaction_date,
+            booking_date: None,
+            counterparty: if payee.is_empty() {
+                None
+            } else {
+                Some(payee.clone())
+            },
+            description: if memo.is_empty() { payee } else { memo },
+            amount,
+            currency: "PLN".to_string(),
+            bank_category: if category.is_empty() {
+                None
+            } else {
+                Some(category.to_string())
+            },
+            bank_reference: None,
+            bank_type: None,
+            state: match get("cleared").to_lowercase().as_str() {
+                "uncleared" => "pending".to_string(),
+                _ => "completed".to_string(),
+            },
+            raw_data,
+        });
+    }
+
+    if transactions.is_empty() {
+        return Err("No valid transactions found in YNAB CSV".into());
+    }
+
+    tracing::info!("Parsed {} YNAB transactions", transactions.len());
+    Ok(transactions)
+}
+
+fn parse_optional_amount(s: &str, decimal: char) -> Result<rust_decimal::Decimal, String> {
+    if s.is_empty() {
+        Ok(rust_decimal::Decimal::ZERO)
+    } else {
+        common::parse_amount(s, decimal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn sample_csv() -> &'static str {
+        "\u{feff}\"Account\",\"Flag\",\"Date\",\"Payee\",\"Category Group/Category\",\"Category Group\",\"Category\",\"Memo\",\"Outflow\",\"Inflow\",\"Cleared\"\n\
+         \"Checking\",\"\",\"21/02/2026\",\"Biedronka\",\"Everyday: Groceries\",\"Everyday\",\"Groceries\",\"\",\"49,47zł\",\"0,00zł\",\"Cleared\"\n\
+         \"Checking\",\"\",\"01/02/2026\",\"ACME\",\"Inflow: Ready to Assign\",\"Inflow\",\"Ready to Assign\",\"Pensja luty\",\"0,00zł\",\"5 000,00zł\",\"Uncleared\"\n"
+    }
+
+    #[test]
+    fn test_parse_ynab_basic() {
+        let result = parse(sample_csv()).expect("should parse successfully");
+        assert_eq!(result.len(), 2);
+
+        assert_eq!(result[0].account, "Checking");
+        assert_eq!(
+            result[0].transaction_date,
+            NaiveDate::from_ymd_opt(2026, 2, 21).unwrap()
+        );
+        assert_eq!(result[0].amount, Decimal::from_str("-49.47").unwrap());
+        assert_eq!(result[0].counterparty.as_deref(), Some("Biedronka"));
+        assert_eq!(result[0].description, "Biedronka");
+        assert_eq!(
+            result[0].bank_category.as_deref(),
+            Some("Everyday: Groceries")
+        );
+
+        // Day-first was decided from the 21/02 row
+        assert_eq!(
+            result[1].transaction_date,
+            NaiveDate::from_ymd_opt(2026, 2, 1).unwrap()
+        );
+        assert_eq!(result[1].amount, Decimal::from_str("5000.00").unwrap());
+        assert_eq!(result[1].description, "Pensja luty");
+        assert_eq!(result[1].state, "pending");
+    }
+
+    #[test]
+    fn test_parse_ynab_empty() {
+        let csv = "Account,Flag,Date,Payee,Category Group/Category,Category Group,Category,Memo,Outflow,Inflow,Cleared\n";
+        assert!(parse(csv).is_err());
+    }
+}
@@ -0,0 +1,340 @@
+use sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, FromQueryResult, Set,
+    Statement,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::entities::{categories, category_overrides, transactions};
+use crate::services::categorize::AUTOMATIC_SOURCES;
+use crate::services::merchants;
+
+const TOP_MERCHANTS: usize = 20;
+
+#[derive(Serialize)]
+pub struct Report {
+    pub sources: Vec<SourceReport>,
+    /// Merchants whose automatic category was changed by hand most often.
+    pub corrected_merchants: Vec<CorrectedMerchant>,
+}
+
+#[derive(Serialize)]
+pub struct SourceReport {
+    pub source: String,
+    /// Rows evaluated: current assignments plus overridden ones.
+    pub evaluated: u64,
+    /// Overridden rows whose category was changed rather than confirmed.
+    pub corrected: u64,
+    pub accuracy: Option<f64>,
+    pub categories: Vec<CategoryScore>,
+    pub confusion: Vec<ConfusionCell>,
+}
+
+#[derive(Serialize)]
+pub struct CategoryScore {
+    pub category_id: i32,
+    pub category: String,
+    /// Rows the source put in this category.
+    pub predicted: u64,
+    /// Rows that belong in this category.
+    pub actual: u64,
+    pub correct: u64,
+    pub precision: Option<f64>,
+    pub recall: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct ConfusionCell {
+    pub predicted_id: i32,
+    pub predicted: String,
+    pub actual_id: i32,
+    pub actual: String,
+    pub count: u64,
+}
+
+#[derive(Serialize)]
+pub struct CorrectedMerchant {
+    pub merchant: String,
+    pub corrections: u64,
+    pub sources: Vec<String>,
+}
+
+#[derive(FromQueryResult)]
+struct Assigned {
+    source: String,
+    category_id: i32,
+    count: i64,
+}
+
+#[derive(FromQueryResult)]
+struct Overridden {
+    previous_source: String,
+    previous_category_id: i32,
+    category_id: i32,
+    counterparty: Option<String>,
+    description: String,
+}
+
+/// Record manual category decisions over rows that had an automatic
+/// category; `before` are the rows as they were before the change.
+pub async fn record_overrides(
+    db: &impl ConnectionTrait,
+    before: &[transactions::Model],
+    category_id: Option<i32>,
+) -> Result<(), String> {
+    let overrides: Vec<_> = before
+        .iter()
+        .filter(|tx| tx.category_id.is_some())
+        .filter_map(|tx| {
+            let source = tx.category_source.as_deref()?;
+            AUTOMATIC_SOURCES
+                .contains(&source)
+                .then(|| override_model(tx.id, tx.category_id, source, category_id))
+        })
+        .collect();
+
+    if overrides.is_empty() {
+        return Ok(());
+    }
+
+    category_overrides::Entity::insert_many(overrides)
+        .exec(db)
+        .await
+        .map_err(|e| format!("Override save error: {}", e))?;
+
+    Ok(())
+}
+
+/// Record a suggestion edited in review: the pick of the categorizer
+/// `source` was overridden before it was ever applied.
+pub async fn record_suggestion_edit(
+    db: &impl ConnectionTrait,
+    transaction_id: i32,
+    suggested_category_id: i32,
+    source: &str,
+    category_id: i32,
+) -> Result<(), String> {
+    category_overrides::Entity::insert(override_model(
+        transaction_id,
+        Some(suggested_category_id),
+        source,
+        Some(category_id),
+    ))
+    .exec(db)
+    .await
+    .map_err(|e| format!("Override save error: {}", e))?;
+
+    Ok(())
+}
+
+fn override_model(
+    transaction_id: i32,
+    previous_category_id: Option<i32>,
+    previous_source: &str,
+    category_id: Option<i32>,
+) -> category_overrides::ActiveModel {
+    category_overrides::ActiveModel {
+        transaction_id: Set(transaction_id),
+        previous_category_id: Set(previous_category_id),
+        previous_source: Set(previous_source.to_string()),
+        category_id: Set(category_id),
+        ..Default::default()
+    }
+}
+
+/// Compare automatic assignments with the manual overrides made since.
+/// Current assignments nobody changed count as correct; overridden rows are
+/// judged by their first override.
+pub async fn report(db: &DatabaseConnection, source: Option<&str>) -> Result<Report, String> {
+    let names: HashMap<i32, String> = categories::Entity::find()
+        .all(db)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+
+    let assigned = Assigned::find_by_statement(Statement::from_string(
+        DatabaseBackend::Postgres,
+        r#"SELECT category_source AS source, category_id, COUNT(*) AS count
+           FROM transactions
+           WHERE category_id IS NOT NULL AND category_source IS NOT NULL
+             AND deleted_at IS NULL
+           GROUP BY category_source, category_id"#,
+    ))
+    .all(db)
+    .await
+    .map_err(|e| format!("Assignment lookup error: {}", e))?;
+
+    let overridden = Overridden::find_by_statement(Statement::from_string(
+        DatabaseBackend::Postgres,
+        r#"SELECT DISTINCT ON (o.transaction_id)
+                  o.previous_source, o.previous_category_id, o.category_id,
+                  t.counterparty, t.description
+           FROM category_overrides o
+           JOIN transactions t ON t.id = o.transaction_id
+           WHERE o.previous_category_id IS NOT NULL AND o.category_id IS NOT NULL
+             AND t.deleted_at IS NULL
+           ORDER BY o.transaction_id, o.id"#,
+    ))
+    .all(db)
+    .await
+    .map_err(|e| format!("Override lookup error: {}", e))?;
+
+    let wanted =
+        |s: &str| source.is_none_or(|wanted| wanted == s) && AUTOMATIC_SOURCES.contains(&s);
+
+    let mut pairs: BTreeMap<&str, Vec<(i32, i32, u64)>> = BTreeMap::new();
+    for row in assigned.iter().filter(|r| wanted(&r.source)) {
+        pairs.entry(&row.source).or_default().push((
+            row.category_id,
+            row.category_id,
+            row.count as u64,
+        ));
+    }
+
+    let mut corrected: HashMap<&str, u64> = HashMap::new();
+    let mut merchants_seen: HashMap<String, (u64, BTreeSet<String>)> = HashMap::new();
+    for row in overridden.iter().filter(|r| wanted(&r.previous_source)) {
+        let source = row.previous_source.as_str();
+        pairs
+            .entry(source)
+            .or_default()
+            .push((row.previous_category_id, row.category_id, 1));
+
+        if row.previous_category_id != row.category_id {
+            *corrected.entry(source).or_default() += 1;
+            if let Some(fingerprint) =
+                merchants::fingerprint(row.counterparty.as_deref(), &row.description)
+            {
+                let merchant = fingerprint
+                    .split_once(':')
+                    .map_or(fingerprint.clone(), |(_, m)| m.to_string());
+                let entry = merchants_seen.entry(merchant).or_default();
+                entry.0 += 1;
+                entry.1.insert(source.to_string());
+            }
+        }
+    }
+
+    let sources = pairs
+        .into_iter()
+        .map(|(source, pairs)| {
+            let scores = score(&pairs);
+            SourceReport {
+                source: source.to_string(),
+                evaluated: scores.total,
+                corrected: corrected.get(source).copied().unwrap_or_default(),
+                accuracy: ratio(scores.correct, scores.total),
+                categories: scores
+                    .categories
+                    .into_iter()
+                    .map(
+                        |(category_id, (predicted, actual, correct))| CategoryScore {
+                            category_id,
+                            category: name_of(&names, category_id),
+                            predicted,
+                            actual,
+                            correct,
+                            precision: ratio(correct, predicted),
+                            recall: ratio(correct, actual),
+                        },
+                    )
+                    .collect(),
+                confusion: scores
+                    .confusion
+                    .into_iter()
+                    .map(|((predicted_id, actual_id), count)| ConfusionCell {
+                        predicted_id,
+                        predicted: name_of(&names, predicted_id),
+                        actual_id,
+                        actual: name_of(&names, actual_id),
+                        count,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let mut corrected_merchants: Vec<CorrectedMerchant> = merchants_seen
+        .into_iter()
+        .map(|(merchant, (corrections, sources))| CorrectedMerchant {
+            merchant,
+            corrections,
+            sources: sources.into_iter().collect(),
+        })
+        .collect();
+    corrected_merchants.sort_by(|a, b| {
+        b.corrections
+            .cmp(&a.corrections)
+            .then_with(|| a.merchant.cmp(&b.merchant))
+    });
+    corrected_merchants.truncate(TOP_MERCHANTS);
+
+    Ok(Report {
+        sources,
+        corrected_merchants,
+    })
+}
+
+struct Scores {
+    total: u64,
+    correct: u64,
+    /// category id → (predicted, actual, correct)
+    categories: BTreeMap<i32, (u64, u64, u64)>,
+    /// (predicted, actual) → count
+    confusion: BTreeMap<(i32, i32), u64>,
+}
+
+/// Tally (predicted, actual, count) triples.
+fn score(pairs: &[(i32, i32, u64)]) -> Scores {
+    let mut scores = Scores {
+        total: 0,
+        correct: 0,
+        categories: BTreeMap::new(),
+        confusion: BTreeMap::new(),
+    };
+
+    for &(predicted, actual, count) in pairs {
+        scores.total += count;
+        scores.categories.entry(predicted).or_default().0 += count;
+        scores.categories.entry(actual).or_default().1 += count;
+        if predicted == actual {
+            scores.correct += count;
+            scores.categories.entry(actual).or_default().2 += count;
+        }
+        *scores.confusion.entry((predicted, actual)).or_default() += count;
+    }
+
+    scores
+}
+
+fn ratio(part: u64, whole: u64) -> Option<f64> {
+    (whole > 0).then(|| part as f64 / whole as f64)
+}
+
+fn name_of(names: &HashMap<i32, String>, id: i32) -> String {
+    names.get(&id).cloned().unwrap_or_else(|| format!("#{id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        // 8 groceries kept, 2 groceries that were really fuel, 1 fuel kept
+        let scores = score(&[(1, 1, 8), (1, 2, 1), (1, 2, 1), (2, 2, 1)]);
+
+        assert_eq!(scores.total, 11);
+        assert_eq!(scores.correct, 9);
+        assert_eq!(scores.categories[&1], (10, 8, 8));
+        assert_eq!(scores.categories[&2], (1, 3, 1));
+        assert_eq!(scores.confusion[&(1, 2)], 2);
+
+        let (predicted, actual, correct) = scores.categories[&2];
+        assert_eq!(ratio(correct, predicted), Some(1.0));
+        assert_eq!(ratio(correct, actual), Some(1.0 / 3.0));
+        assert_eq!(ratio(0, 0), None);
+    }
+}
@@ -0,0 +1,87 @@
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Hex-encoded SHA-256 of the raw file bytes, used as the archive key.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Archive key for a digest: "ab/abcdef…", fanned out by the first byte so a
+/// single directory never holds every statement ever uploaded.
+pub fn key_for(sha256: &str) -> String {
+    format!("{}/{}", &sha256[..2], sha256)
+}
+
+fn path_for(archive_dir: &Path, key: &str) -> PathBuf {
+    archive_dir.join(key)
+}
+
+/// Store an uploaded file under its content address and return the archive key.
+///
+/// `sha256` must be the digest of `bytes` as returned by [`sha256_hex`]. Writing
+/// is idempotent: an existing file with the same key already holds the exact
+/// same bytes, so it is left untouched.
+pub async fn store(archive_dir: &Path, sha256: &str, bytes: &[u8]) -> Result<String, String> {
+    let key = key_for(sha256);
+    let path = path_for(archive_dir, &key);
+
+    if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+        return Ok(key);
+    }
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create archive dir {}: {}", parent.display(), e))?;
+    }
+
+    // Write to a temp file first so a crash never leaves a truncated original
+    let tmp = path.with_extension("partial");
+    tokio::fs::write(&tmp, bytes)
+        .await
+        .map_err(|e| format!("Failed to write archive file {}: {}", tmp.display(), e))?;
+    tokio::fs::rename(&tmp, &path)
+        .await
+        .map_err(|e| format!("Failed to finalize archive file {}: {}", path.display(), e))?;
+
+    Ok(key)
+}
+
+/// Read an archived original back by its key.
+pub async fn load(archive_dir: &Path, key: &str) -> Result<Vec<u8>, String> {
+    let path = path_for(archive_dir, key);
+    tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Failed to read archive file {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_for_fans_out_by_prefix() {
+        let sha = sha256_hex(b"Data transakcji;Kwota\n");
+        assert_eq!(sha.len(), 64);
+        assert_eq!(key_for(&sha), format!("{}/{}", &sha[..2], sha));
+    }
+
+    #[tokio::test]
+    async fn test_store_and_load_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("ft-archive-{}", std::process::id()));
+        let bytes = "Kryteria transakcji: wszystkie\n".as_bytes();
+
+        let sha = sha256_hex(bytes);
+
+        let key = store(&dir, &sha, bytes)
+            .await
+            .expect("store should succeed");
+        let again = store(&dir, &sha, bytes)
+            .await
+            .expect("second store is a no-op");
+        assert_eq!(key, again);
+        assert_eq!(load(&dir, &key).await.unwrap(), bytes);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,271 @@
+use axum::body::Bytes;
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use std::sync::Arc;
+
+use crate::config::AttachmentConfig;
+use crate::entities::attachments;
+use crate::services::archive::sha256_hex;
+
+const MAX_FILENAME: usize = 200;
+
+/// The configured attachment backend plus the upload size limit.
+#[derive(Clone)]
+pub struct Store {
+    inner: Arc<dyn ObjectStore>,
+    pub max_bytes: usize,
+}
+
+impl Store {
+    pub fn open(config: &AttachmentConfig) -> Result<Self, String> {
+        let inner: Arc<dyn ObjectStore> = match config.store.as_str() {
+            "local" => {
+                std::fs::create_dir_all(&config.dir).map_err(|e| {
+                    format!(
+                        "Failed to create attachment dir {}: {}",
+                        config.dir.display(),
+                        e
+                    )
+                })?;
+                Arc::new(
+                    LocalFileSystem::new_with_prefix(&config.dir)
+                        .map_err(|e| format!("Attachment store error: {}", e))?,
+                )
+            }
+            "s3" => {
+                let bucket = config
+                    .s3_bucket
+                    .as_deref()
+                    .ok_or("S3_BUCKET is required when ATTACHMENT_STORE=s3")?;
+                let mut builder = AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .with_region(&config.s3_region);
+                if let Some(endpoint) = &config.s3_endpoint {
+                    // MinIO and friends are usually plain HTTP with path-style URLs
+                    builder = builder.with_endpoint(endpoint).with_allow_http(true);
+                }
+                if let (Some(key), Some(secret)) =
+                    (&config.s3_access_key_id, &config.s3_secret_access_key)
+                {
+                    builder = builder
+                        .with_access_key_id(key)
+                        .with_secret_access_key(secret);
+                }
+                Arc::new(
+                    builder
+                        .build()
+                        .map_err(|e| format!("Attachment store error: {}", e))?,
+                )
+            }
+            other => {
+                return Err(format!(
+                    "Unknown ATTACHMENT_STORE '{other}' (expected local or s3)"
+                ))
+            }
+        };
+
+        Ok(Self {
+            inner,
+            max_bytes: config.max_bytes,
+        })
+    }
+}
+
+/// Recognise a receipt or invoice from its first bytes. Only JPEG, PNG, WebP,
+/// HEIC and PDF are accepted; the MIME type the client sends is ignored.
+pub fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some("image/png"),
+        [b'%', b'P', b'D', b'F', b'-', ..] => Some("application/pdf"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        [_, _, _, _, b'f', b't', b'y', b'p', brand @ ..]
+            if brand.starts_with(b"heic")
+                || brand.starts_with(b"heix")
+                || brand.starts_with(b"mif1") =>
+        {
+            Some("image/heic")
+        }
+        _ => None,
+    }
+}
+
+/// Check an upload against the size limit and the allowed types and return
+/// its content type.
+pub fn validate(bytes: &[u8], max_bytes: usize) -> Result<&'static str, String> {
+    if bytes.is_empty() {
+        return Err("File is empty".into());
+    }
+    if bytes.len() > max_bytes {
+        return Err(format!(
+            "File is {} bytes; the limit is {} bytes",
+            bytes.len(),
+            max_bytes
+        ));
+    }
+    sniff(bytes).ok_or_else(|| "Only JPEG, PNG, WebP, HEIC images and PDFs are accepted".into())
+}
+
+/// The last path segment of an uploaded filename, without control characters.
+pub fn clean_filename(filename: &str) -> String {
+    let name: String = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILENAME)
+        .collect();
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        "attachment".into()
+    } else {
+        name.to_string()
+    }
+}
+
+fn key_for(transaction_id: i32, sha256: &str) -> String {
+    format!("transactions/{transaction_id}/{sha256}")
+}
+
+pub async fn for_transaction(
+    db: &DatabaseConnection,
+    transaction_id: i32,
+) -> Result<Vec<attachments::Model>, String> {
+    attachments::Entity::find()
+        .filter(attachments::Column::TransactionId.eq(transaction_id))
+        .order_by_asc(attachments::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("Attachment lookup error: {}", e))
+}
+
+/// Store a validated file for a transaction. Uploading the same file to the
+/// same transaction again returns the existing attachment.
+pub async fn save(
+    db: &DatabaseConnection,
+    store: &Store,
+    transaction_id: i32,
+    filename: &str,
+    content_type: &str,
+    bytes: Vec<u8>,
+) -> Result<attachments::Model, String> {
+    let sha256 = sha256_hex(&bytes);
+    let existing = attachments::Entity::find()
+        .filter(attachments::Column::TransactionId.eq(transaction_id))
+        .filter(attachments::Column::Sha256.eq(&sha256))
+        .one(db)
+        .await
+        .map_err(|e| format!("Attachment lookup error: {}", e))?;
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
+
+    let key = key_for(transaction_id, &sha256);
+    let size = bytes.len() as i64;
+    store
+        .inner
+        .put(&ObjectPath::from(key.as_str()), PutPayload::from(bytes))
+        .await
+        .map_err(|e| format!("Attachment write error: {}", e))?;
+
+    let model = attachments::ActiveModel {
+        transaction_id: Set(transaction_id),
+        filename: Set(clean_filename(filename)),
+        content_type: Set(content_type.to_string()),
+        size: Set(size),
+        sha256: Set(sha256),
+        storage_key: Set(key.clone()),
+        ..Default::default()
+    };
+    match attachments::Entity::insert(model)
+        .exec_with_returning(db)
+        .await
+    {
+        Ok(attachment) => Ok(attachment),
+        Err(e) => {
+            // Don't leave an object nothing points at
+            let _ = store.inner.delete(&ObjectPath::from(key.as_str())).await;
+            Err(format!("Attachment save error: {}", e))
+        }
+    }
+}
+
+pub async fn load(store: &Store, attachment: &attachments::Model) -> Result<Bytes, String> {
+    store
+        .inner
+        .get(&ObjectPath::from(attachment.storage_key.as_str()))
+        .await
+        .map_err(|e| format!("Attachment read error: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Attachment read error: {}", e))
+}
+
+/// Delete an attachment's file and row. A file that is already gone is not
+/// an error.
+pub async fn remove(
+    db: &DatabaseConnection,
+    store: &Store,
+    attachment: &attachments::Model,
+) -> Result<(), String> {
+    match store
+        .inner
+        .delete(&ObjectPath::from(attachment.storage_key.as_str()))
+        .await
+    {
+        Ok(()) | Err(object_store::Error::NotFound { .. }) => {}
+        Err(e) => return Err(format!("Attachment delete error: {}", e)),
+    }
+
+    attachments::Entity::delete_by_id(attachment.id)
+        .exec(db)
+        .await
+        .map_err(|e| format!("Attachment delete error: {}", e))?;
+
+    Ok(())
+}
+
+/// Delete every attachment of a transaction, files included. Call this
+/// before deleting the transaction itself; the foreign key only cascades
+/// the rows.
+pub async fn remove_all(
+    db: &DatabaseConnection,
+    store: &Store,
+    transaction_id: i32,
+) -> Result<usize, String> {
+    let attachments = for_transaction(db, transaction_id).await?;
+    for attachment in &attachments {
+        remove(db, store, attachment).await?;
+    }
+    Ok(attachments.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let pdf = b"%PDF-1.7\n...".to_vec();
+        assert_eq!(validate(&pdf, 1024), Ok("application/pdf"));
+        assert_eq!(sniff(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff(b"RIFF\x10\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff(b"\0\0\0\x18ftypheic"), Some("image/heic"));
+
+        assert!(validate(b"<html>", 1024).is_err());
+        assert!(validate(&pdf, 4).is_err());
+        assert!(validate(b"", 1024).is_err());
+    }
+
+    #[test]
+    fn test_clean_filename() {
+        assert_eq!(clean_filename("C:\\Users\\me\\paragon.jpg"), "paragon.jpg");
+        assert_eq!(clean_filename("../../etc/passwd"), "passwd");
+        assert_eq!(clean_filename("faktura\n 01.pdf"), "faktura 01.pdf");
+        assert_eq!(clean_filename(".."), "attachment");
+    }
+}
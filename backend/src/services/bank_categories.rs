@@ -0,0 +1,151 @@
+use sea_orm::sea_query::{Expr, Query};
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseBackend, DatabaseConnection, EntityTrait, FromQueryResult,
+    QueryFilter, Statement,
+};
+use std::collections::HashMap;
+
+use crate::entities::{accounts, bank_category_mappings, transactions};
+use crate::services::history::{self, Actor};
+
+/// `category_source` of rows whose category comes from the bank.
+pub const SOURCE_BANK: &str = "bank";
+
+#[derive(FromQueryResult)]
+pub struct UnmappedCategory {
+    pub bank: String,
+    pub bank_category: String,
+    pub count: i64,
+}
+
+/// The bank category mappings, keyed by (bank, bank category). The bank is the
+/// account name the bank's parser imports into ("alior", "pekao", …).
+pub struct BankCategoryMap(HashMap<(String, String), i32>);
+
+impl BankCategoryMap {
+    pub async fn load(db: &DatabaseConnection) -> Result<Self, String> {
+        let mappings = bank_category_mappings::Entity::find()
+            .all(db)
+            .await
+            .map_err(|e| format!("Bank category mapping lookup error: {}", e))?;
+
+        Ok(Self(
+            mappings
+                .into_iter()
+                .map(|m| ((m.bank, m.bank_category), m.category_id))
+                .collect(),
+        ))
+    }
+
+    pub fn get(&self, bank: &str, bank_category: Option<&str>) -> Option<i32> {
+        let bank_category = bank_category?;
+        self.0
+            .get(&(bank.to_string(), bank_category.to_string()))
+            .copied()
+    }
+}
+
+/// Set `category_id` on the already imported rows a mapping covers (or clear
+/// it, when the mapping was removed). Only rows whose category still comes
+/// from the bank, and that aren't in the trash, are touched. Returns the number of transactions updated.
+pub async fn reapply(
+    db: &DatabaseConnection,
+    bank: &str,
+    bank_category: &str,
+    category_id: Option<i32>,
+    actor: &Actor,
+) -> Result<u64, String> {
+    let bank_accounts = Query::select()
+        .column(accounts::Column::Id)
+        .from(accounts::Entity)
+        .and_where(accounts::Column::Name.eq(bank))
+        .to_owned();
+    let covered = Condition::all()
+        .add(transactions::Column::AccountId.in_subquery(bank_accounts))
+        .add(transactions::Column::BankCategory.eq(bank_category))
+        .add(transactions::Column::DeletedAt.is_null())
+        .add(
+            Condition::any()
+                .add(transactions::Column::CategorySource.is_null())
+                .add(transactions::Column::CategorySource.eq(SOURCE_BANK)),
+        );
+
+    let rows = transactions::Entity::find()
+        .filter(covered.clone())
+        .all(db)
+        .await
+        .map_err(|e| format!("Transaction lookup error: {}", e))?;
+
+    let result = transactions::Entity::update_many()
+        .col_expr(transactions::Column::CategoryId, Expr::value(category_id))
+        .col_expr(
+            transactions::Column::CategorySource,
+            Expr::value(SOURCE_BANK),
+        )
+        .filter(covered)
+        .exec(db)
+        .await
+        .map_err(|e| format!("Update error: {}", e))?;
+
+    history::record_each(db, actor, &rows, |tx| {
+        tx.category_id = category_id;
+        tx.category_source = Some(SOURCE_BANK.to_string());
+    })
+    .await?;
+
+    Ok(result.rows_affected)
+}
+
+/// Bank categories seen on imported rows that have no mapping yet, most
+/// frequent first. Rows migrated from other apps are resolved through the
+/// import mappings instead and are left out.
+pub async fn unmapped(db: &DatabaseConnection) -> Result<Vec<UnmappedCategory>, String> {
+    UnmappedCategory::find_by_statement(Statement::from_string(
+        DatabaseBackend::Postgres,
+        r#"SELECT a.name AS bank, t.bank_category, COUNT(*) AS count
+           FROM transactions t
+           JOIN accounts a ON a.id = t.account_id
+           LEFT JOIN bank_category_mappings m
+             ON m.bank = a.name AND m.bank_category = t.bank_category
+           WHERE t.bank_category IS NOT NULL
+             AND m.id IS NULL
+             AND t.raw_data->>'source' IS NULL
+             AND t.deleted_at IS NULL
+           GROUP BY a.name, t.bank_category
+           ORDER BY count DESC, a.name, t.bank_category"#,
+    ))
+    .all(db)
+    .await
+    .map_err(|e| format!("Unmapped category lookup error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(bank: &str, bank_category: &str, category_id: i32) -> ((String, String), i32) {
+        ((bank.to_string(), bank_category.to_string()), category_id)
+    }
+
+    #[test]
+    fn test_get() {
+        let map = BankCategoryMap(HashMap::from([
+            mapping("pekao", "Żywność", 1),
+            mapping("pekao", "Transport", 2),
+            mapping("alior", "Żywność", 3),
+        ]));
+
+        // Each bank resolves its own mapping for a shared label
+        assert_eq!(map.get("pekao", Some("Żywność")), Some(1));
+        assert_eq!(map.get("alior", Some("Żywność")), Some(3));
+        assert_eq!(map.get("pekao", Some("Transport")), Some(2));
+
+        // No fallback to another bank's mapping, or to an unscoped one
+        assert_eq!(map.get("alior", Some("Transport")), None);
+        assert_eq!(map.get("mbank", Some("Żywność")), None);
+
+        // Labels match exactly, and rows without a bank category map to nothing
+        assert_eq!(map.get("pekao", Some("żywność")), None);
+        assert_eq!(map.get("pekao", None), None);
+    }
+}
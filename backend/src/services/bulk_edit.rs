@@ -0,0 +1,380 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::entities::transactions;
+use crate::services::history::{self, Actor};
+use crate::services::tags::CHUNK;
+use crate::services::{accuracy, merchants, review, tags};
+
+/// Rows listed in a bulk edit response.
+pub const PREVIEW_LIMIT: usize = 100;
+
+/// What to change on every selected transaction; `None` leaves a field alone.
+/// Tag names must already be normalised.
+#[derive(Debug, Default)]
+pub struct Edit {
+    /// `Some(None)` uncategorises.
+    pub category_id: Option<Option<i32>>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+    /// `Some(None)` clears the notes.
+    pub notes: Option<Option<String>>,
+    /// One of the [`review`] statuses.
+    pub review_status: Option<&'static str>,
+}
+
+impl Edit {
+    pub fn is_empty(&self) -> bool {
+        self.category_id.is_none()
+            && self.add_tags.is_empty()
+            && self.remove_tags.is_empty()
+            && self.notes.is_none()
+            && self.review_status.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub from: Value,
+    pub to: Value,
+}
+
+#[derive(Serialize)]
+pub struct RowChange {
+    pub id: i32,
+    pub transaction_date: NaiveDate,
+    pub description: String,
+    pub amount: Decimal,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Serialize)]
+pub struct Outcome {
+    pub dry_run: bool,
+    pub matched: usize,
+    /// Rows with at least one field changed (or that would be, on a dry run).
+    pub changed: usize,
+    /// The first changed rows, field by field.
+    pub rows: Vec<RowChange>,
+}
+
+/// The fields `edit` changes on `tx`, whose current tags are `tags`. Setting
+/// a category always makes it manual, so an automatic category confirmed by
+/// hand counts as a change of source.
+pub fn diff(tx: &transactions::Model, tags: &[String], edit: &Edit) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    if let Some(category_id) = edit.category_id {
+        if tx.category_id != category_id {
+            changes.push(FieldChange {
+                field: "category_id",
+                from: json!(tx.category_id),
+                to: json!(category_id),
+            });
+        }
+        if tx.category_source.as_deref() != Some("manual") {
+            changes.push(FieldChange {
+                field: "category_source",
+                from: json!(tx.category_source),
+                to: json!("manual"),
+            });
+        }
+    }
+
+    let mut new_tags: Vec<String> = tags
+        .iter()
+        .filter(|t| !edit.remove_tags.contains(t))
+        .cloned()
+        .collect();
+    for tag in &edit.add_tags {
+        if !new_tags.contains(tag) {
+            new_tags.push(tag.clone());
+        }
+    }
+    new_tags.sort();
+    let mut old_tags = tags.to_vec();
+    old_tags.sort();
+    if new_tags != old_tags {
+        changes.push(FieldChange {
+            field: "tags",
+            from: json!(old_tags),
+            to: json!(new_tags),
+        });
+    }
+
+    if let Some(ref notes) = edit.notes {
+        if &tx.notes != notes {
+            changes.push(FieldChange {
+                field: "notes",
+                from: json!(tx.notes),
+                to: json!(notes),
+            });
+        }
+    }
+
+    if let Some(status) = edit.review_status {
+        if tx.review_status != status {
+            changes.push(FieldChange {
+                field: "review_status",
+                from: json!(tx.review_status),
+                to: json!(status),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Apply `edit` to `rows` in one database transaction, or only report what
+/// would change when `dry_run` is set.
+pub async fn run(
+    db: &DatabaseConnection,
+    rows: Vec<transactions::Model>,
+    edit: &Edit,
+    dry_run: bool,
+    actor: &Actor,
+) -> Result<Outcome, String> {
+    let ids: Vec<i32> = rows.iter().map(|tx| tx.id).collect();
+    let current_tags = tags::names_for(db, &ids).await?;
+
+    let mut changed: Vec<(transactions::Model, Vec<FieldChange>)> = rows
+        .into_iter()
+        .filter_map(|tx| {
+            let tags = current_tags.get(&tx.id).map_or(&[][..], Vec::as_slice);
+            let changes = diff(&tx, tags, edit);
+            (!changes.is_empty()).then_some((tx, changes))
+        })
+        .collect();
+
+    if !dry_run {
+        let txn = db
+            .begin()
+            .await
+            .map_err(|e| format!("Transaction error: {}", e))?;
+        apply(&txn, &changed, edit, actor).await?;
+        txn.commit()
+            .await
+            .map_err(|e| format!("Transaction error: {}", e))?;
+    }
+
+    let outcome = Outcome {
+        dry_run,
+        matched: ids.len(),
+        changed: changed.len(),
+        rows: Vec::new(),
+    };
+    changed.truncate(PREVIEW_LIMIT);
+
+    Ok(Outcome {
+        rows: changed
+            .into_iter()
+            .map(|(tx, changes)| RowChange {
+                id: tx.id,
+                transaction_date: tx.transaction_date,
+                description: tx.description,
+                amount: tx.amount,
+                changes,
+            })
+            .collect(),
+        ..outcome
+    })
+}
+
+async fn apply(
+    db: &impl ConnectionTrait,
+    changed: &[(transactions::Model, Vec<FieldChange>)],
+    edit: &Edit,
+    actor: &Actor,
+) -> Result<(), String> {
+    let touched = |fields: &[&str]| -> Vec<&transactions::Model> {
+        changed
+            .iter()
+            .filter(|(_, changes)| changes.iter().any(|c| fields.contains(&c.field)))
+            .map(|(tx, _)| tx)
+            .collect()
+    };
+
+    if let Some(category_id) = edit.category_id {
+        let rows: Vec<transactions::Model> = touched(&["category_id", "category_source"])
+            .into_iter()
+            .cloned()
+            .collect();
+        accuracy::record_overrides(db, &rows, category_id).await?;
+        update(
+            db,
+            &ids_of(&rows),
+            &[
+                (transactions::Column::CategoryId, Expr::value(category_id)),
+                (transactions::Column::CategorySource, Expr::value("manual")),
+                (
+                    transactions::Column::CategoryRuleId,
+                    Expr::value(Option::<i32>::None),
+                ),
+            ],
+        )
+        .await?;
+        history::record_each(db, actor, &rows, |tx| {
+            tx.category_id = category_id;
+            tx.category_source = Some("manual".to_string());
+            tx.category_rule_id = None;
+        })
+        .await?;
+        if let Some(category_id) = category_id {
+            for tx in &rows {
+                merchants::remember(db, tx, category_id).await?;
+            }
+        }
+    }
+
+    let tagged = ids_of(touched(&["tags"]));
+    if !tagged.is_empty() {
+        let add: Vec<i32> = tags::ensure(db, &edit.add_tags)
+            .await?
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        let remove: Vec<i32> = tags::find(db, &edit.remove_tags)
+            .await?
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        tags::remove(db, &tagged, &remove).await?;
+        tags::add(db, &tagged, &add).await?;
+    }
+
+    if let Some(ref notes) = edit.notes {
+        let rows: Vec<transactions::Model> = touched(&["notes"]).into_iter().cloned().collect();
+        update(
+            db,
+            &ids_of(&rows),
+            &[(transactions::Column::Notes, Expr::value(notes.clone()))],
+        )
+        .await?;
+        history::record_each(db, actor, &rows, |tx| tx.notes = notes.clone()).await?;
+    }
+
+    if let Some(status) = edit.review_status {
+        let rows: Vec<transactions::Model> =
+            touched(&["review_status"]).into_iter().cloned().collect();
+        update(
+            db,
+            &ids_of(&rows),
+            &[
+                (transactions::Column::ReviewStatus, Expr::value(status)),
+                (
+                    transactions::Column::ReviewedAt,
+                    Expr::value(review::reviewed_at(status)),
+                ),
+            ],
+        )
+        .await?;
+        history::record_each(db, actor, &rows, |tx| tx.review_status = status.to_string()).await?;
+    }
+
+    Ok(())
+}
+
+fn ids_of<'a>(rows: impl IntoIterator<Item = &'a transactions::Model>) -> Vec<i32> {
+    rows.into_iter().map(|tx| tx.id).collect()
+}
+
+async fn update(
+    db: &impl ConnectionTrait,
+    ids: &[i32],
+    values: &[(transactions::Column, SimpleExpr)],
+) -> Result<(), String> {
+    for chunk in ids.chunks(CHUNK) {
+        let mut query = transactions::Entity::update_many();
+        for (column, value) in values {
+            query = query.col_expr(*column, value.clone());
+        }
+        query
+            .filter(transactions::Column::Id.is_in(chunk.iter().copied()))
+            .exec(db)
+            .await
+            .map_err(|e| format!("Bulk update error: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx() -> transactions::Model {
+        transactions::Model {
+            id: 1,
+            hash: String::new(),
+            account_id: 1,
+            transaction_date: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+            booking_date: None,
+            counterparty: Some("ORLEN".into()),
+            description: "ORLEN STACJA".into(),
+            amount: Decimal::from(-200),
+            currency: "PLN".into(),
+            category_id: Some(3),
+            category_source: Some("ai".into()),
+            bank_category: None,
+            bank_reference: None,
+            bank_type: None,
+            state: None,
+            raw_data: None,
+            imported_at: None,
+            import_log_id: None,
+            import_row: None,
+            category_rule_id: None,
+            notes: None,
+            reviewed_at: None,
+            review_status: "new".into(),
+            source: "import".into(),
+            deleted_at: None,
+        }
+    }
+
+    fn fields(changes: &[FieldChange]) -> Vec<&'static str> {
+        changes.iter().map(|c| c.field).collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let tags = vec!["business".to_string()];
+
+        let edit = Edit {
+            category_id: Some(Some(4)),
+            add_tags: vec!["business".into(), "reimbursable".into()],
+            review_status: Some(review::FLAGGED),
+            ..Default::default()
+        };
+        let changes = diff(&tx(), &tags, &edit);
+        assert_eq!(
+            fields(&changes),
+            ["category_id", "category_source", "tags", "review_status"]
+        );
+        assert_eq!(changes[2].to, json!(["business", "reimbursable"]));
+
+        // Confirming the AI category only changes the source
+        let confirm = Edit {
+            category_id: Some(Some(3)),
+            ..Default::default()
+        };
+        assert_eq!(fields(&diff(&tx(), &tags, &confirm)), ["category_source"]);
+
+        // Nothing to do: tag already there, notes already empty
+        let noop = Edit {
+            add_tags: vec!["business".into()],
+            remove_tags: vec!["kids".into()],
+            notes: Some(None),
+            review_status: Some(review::NEW),
+            ..Default::default()
+        };
+        assert!(diff(&tx(), &tags, &noop).is_empty());
+        assert!(Edit::default().is_empty());
+    }
+}
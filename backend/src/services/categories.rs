@@ -0,0 +1,428 @@
+use rust_decimal::Decimal;
+use sea_orm::sea_query::{Expr, IntoColumnRef, Query};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, TransactionTrait,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use tracing::info;
+
+use crate::entities::{
+    bank_category_mappings, categories, category_overrides, category_rules, category_suggestions,
+    import_mappings, merchant_categories, transaction_splits, transactions,
+};
+use crate::services::history::{self, Actor};
+
+/// Rows pointing at a category that the user would lose track of if it were
+/// deleted outright.
+#[derive(Debug, Default, Serialize)]
+pub struct References {
+    pub children: u64,
+    pub transactions: u64,
+    pub splits: u64,
+    pub rules: u64,
+    pub bank_mappings: u64,
+    pub import_mappings: u64,
+}
+
+impl References {
+    pub fn is_empty(&self) -> bool {
+        self.children
+            + self.transactions
+            + self.splits
+            + self.rules
+            + self.bank_mappings
+            + self.import_mappings
+            == 0
+    }
+}
+
+/// Rows moved from the merged category to the target.
+#[derive(Debug, Default, Serialize)]
+pub struct MergeResult {
+    pub children: u64,
+    pub transactions: u64,
+    pub splits: u64,
+    pub rules: u64,
+    pub bank_mappings: u64,
+    pub import_mappings: u64,
+    pub suggestions: u64,
+    pub merchants: u64,
+}
+
+pub async fn references(db: &DatabaseConnection, id: i32) -> Result<References, String> {
+    let count_error = |e: sea_orm::DbErr| format!("Reference count error: {}", e);
+
+    Ok(References {
+        children: categories::Entity::find()
+            .filter(categories::Column::ParentId.eq(id))
+            .count(db)
+            .await
+            .map_err(count_error)?,
+        transactions: transactions::Entity::find()
+            .filter(transactions::Column::CategoryId.eq(id))
+            .count(db)
+            .await
+            .map_err(count_error)?,
+        splits: transaction_splits::Entity::find()
+            .filter(transaction_splits::Column::CategoryId.eq(id))
+            .count(db)
+            .await
+            .map_err(count_error)?,
+        rules: category_rules::Entity::find()
+            .filter(category_rules::Column::CategoryId.eq(id))
+            .count(db)
+            .await
+            .map_err(count_error)?,
+        bank_mappings: bank_category_mappings::Entity::find()
+            .filter(bank_category_mappings::Column::CategoryId.eq(id))
+            .count(db)
+            .await
+            .map_err(count_error)?,
+        import_mappings: import_mappings::Entity::find()
+            .filter(import_mappings::Column::CategoryId.eq(id))
+            .count(db)
+            .await
+            .map_err(count_error)?,
+    })
+}
+
+/// Move everything that points at `source` to `target` and delete `source`,
+/// all in one database transaction. Subcategories of `source` move under
+/// `target`; a `target` nested inside `source` first takes its place.
+pub async fn merge(
+    db: &DatabaseConnection,
+    source: &categories::Model,
+    target: &categories::Model,
+    actor: &Actor,
+) -> Result<MergeResult, String> {
+    let all = categories::Entity::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Category lookup error: {}", e))?;
+    let nested = Tree::new(&all).is_descendant(target.id, source.id);
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    if nested {
+        categories::Entity::update_many()
+            .col_expr(categories::Column::ParentId, Expr::value(source.parent_id))
+            .filter(categories::Column::Id.eq(target.id))
+            .exec(&txn)
+            .await
+            .map_err(|e| format!("Reassign error: {}", e))?;
+    }
+
+    // What the merge rewrites on transactions, for their history
+    let moved = transactions::Entity::find()
+        .filter(transactions::Column::CategoryId.eq(source.id))
+        .all(&txn)
+        .await
+        .map_err(|e| format!("Transaction lookup error: {}", e))?;
+    let split_of_source = Query::select()
+        .column(transaction_splits::Column::TransactionId)
+        .from(transaction_splits::Entity)
+        .and_where(transaction_splits::Column::CategoryId.eq(source.id))
+        .to_owned();
+    let mut split: BTreeMap<i32, Vec<transaction_splits::Model>> = BTreeMap::new();
+    for s in transaction_splits::Entity::find()
+        .filter(transaction_splits::Column::TransactionId.in_subquery(split_of_source))
+        .order_by_asc(transaction_splits::Column::Id)
+        .all(&txn)
+        .await
+        .map_err(|e| format!("Split lookup error: {}", e))?
+    {
+        split.entry(s.transaction_id).or_default().push(s);
+    }
+
+    let result = MergeResult {
+        children: reassign::<categories::Entity, _>(
+            &txn,
+            categories::Column::ParentId,
+            source.id,
+            target.id,
+        )
+        .await?,
+        transactions: reassign::<transactions::Entity, _>(
+            &txn,
+            transactions::Column::CategoryId,
+            source.id,
+            target.id,
+        )
+        .await?,
+        splits: reassign::<transaction_splits::Entity, _>(
+            &txn,
+            transaction_splits::Column::CategoryId,
+            source.id,
+            target.id,
+        )
+        .await?,
+        rules: reassign::<category_rules::Entity, _>(
+            &txn,
+            category_rules::Column::CategoryId,
+            source.id,
+            target.id,
+        )
+        .await?,
+        bank_mappings: reassign::<bank_category_mappings::Entity, _>(
+            &txn,
+            bank_category_mappings::Column::CategoryId,
+            source.id,
+            target.id,
+        )
+        .await?,
+        import_mappings: reassign::<import_mappings::Entity, _>(
+            &txn,
+            import_mappings::Column::CategoryId,
+            source.id,
+            target.id,
+        )
+        .await?,
+        suggestions: reassign::<category_suggestions::Entity, _>(
+            &txn,
+            category_suggestions::Column::CategoryId,
+            source.id,
+            target.id,
+        )
+        .await?,
+        merchants: reassign::<merchant_categories::Entity, _>(
+            &txn,
+            merchant_categories::Column::CategoryId,
+            source.id,
+            target.id,
+        )
+        .await?,
+    };
+    for column in [
+        category_overrides::Column::PreviousCategoryId,
+        category_overrides::Column::CategoryId,
+    ] {
+        reassign::<category_overrides::Entity, _>(&txn, column, source.id, target.id).await?;
+    }
+
+    history::record_each(&txn, actor, &moved, |tx| tx.category_id = Some(target.id)).await?;
+    let split_changes = split
+        .into_iter()
+        .map(|(transaction_id, before)| {
+            let after: Vec<_> = before
+                .iter()
+                .cloned()
+                .map(|mut s| {
+                    if s.category_id == Some(source.id) {
+                        s.category_id = Some(target.id);
+                    }
+                    s
+                })
+                .collect();
+            history::Entry {
+                transaction_id,
+                field: history::FIELD_SPLITS,
+                old_value: history::splits_value(&before),
+                new_value: history::splits_value(&after),
+            }
+        })
+        .collect();
+    history::record(&txn, actor, split_changes).await?;
+
+    categories::Entity::delete_by_id(source.id)
+        .exec(&txn)
+        .await
+        .map_err(|e| format!("Category delete error: {}", e))?;
+
+    txn.commit()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    info!(
+        "Merged category '{}' into '{}' ({} transactions)",
+        source.name, target.name, result.transactions
+    );
+    Ok(result)
+}
+
+/// Parent/child links between categories.
+pub struct Tree {
+    parents: HashMap<i32, Option<i32>>,
+    children: HashMap<i32, Vec<i32>>,
+}
+
+impl Tree {
+    pub fn new(categories: &[categories::Model]) -> Self {
+        let parents: HashMap<i32, Option<i32>> =
+            categories.iter().map(|c| (c.id, c.parent_id)).collect();
+
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+        for category in categories {
+            // A parent outside the set (e.g. archived) makes this a root
+            if let Some(parent) = category.parent_id.filter(|p| parents.contains_key(p)) {
+                children.entry(parent).or_default().push(category.id);
+            }
+        }
+
+        Self { parents, children }
+    }
+
+    /// `id` and its ancestors, root first.
+    pub fn path(&self, id: i32) -> Vec<i32> {
+        let mut path = vec![id];
+        let mut current = id;
+        while let Some(Some(parent)) = self.parents.get(&current) {
+            // A cycle can only come from hand-edited data; stop rather than loop
+            if !self.parents.contains_key(parent) || path.contains(parent) {
+                break;
+            }
+            path.push(*parent);
+            current = *parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// 1 for top-level categories.
+    pub fn depth(&self, id: i32) -> usize {
+        self.path(id).len()
+    }
+
+    /// The ancestor of `id` at `depth`, or `id` itself if it is shallower.
+    pub fn at_depth(&self, id: i32, depth: usize) -> i32 {
+        let path = self.path(id);
+        path[depth.clamp(1, path.len()) - 1]
+    }
+
+    pub fn is_leaf(&self, id: i32) -> bool {
+        !self.children.contains_key(&id)
+    }
+
+    pub fn children(&self, id: i32) -> &[i32] {
+        self.children.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// True if `id` sits anywhere below `ancestor`.
+    pub fn is_descendant(&self, id: i32, ancestor: i32) -> bool {
+        id != ancestor && self.path(id).contains(&ancestor)
+    }
+}
+
+/// Expense totals keyed by category (`None` = uncategorised).
+pub type Totals = BTreeMap<Option<i32>, (Decimal, i64)>;
+
+/// Regroup per-category totals along the tree. With `depth`, categories
+/// below that level are counted in their ancestor at that level. With
+/// `rollup`, every category also carries the totals of everything below it,
+/// so parents and children overlap.
+pub fn roll_up(totals: &Totals, tree: &Tree, depth: Option<usize>, rollup: bool) -> Totals {
+    let mut out = Totals::new();
+    for (&id, &(total, count)) in totals {
+        let targets = match id {
+            None => vec![None],
+            Some(id) if rollup => tree
+                .path(id)
+                .into_iter()
+                .take(depth.unwrap_or(usize::MAX))
+                .map(Some)
+                .collect(),
+            Some(id) => vec![Some(depth.map_or(id, |d| tree.at_depth(id, d)))],
+        };
+
+        for target in targets {
+            let entry = out.entry(target).or_default();
+            entry.0 += total;
+            entry.1 += count;
+        }
+    }
+    out
+}
+
+async fn reassign<E, C>(
+    db: &impl ConnectionTrait,
+    column: C,
+    from: i32,
+    to: i32,
+) -> Result<u64, String>
+where
+    E: EntityTrait<Column = C>,
+    C: ColumnTrait + IntoColumnRef,
+{
+    let result = E::update_many()
+        .col_expr(column, Expr::value(to))
+        .filter(column.eq(from))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Reassign error: {}", e))?;
+
+    Ok(result.rows_affected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(id: i32, parent_id: Option<i32>) -> categories::Model {
+        categories::Model {
+            id,
+            name: format!("c{id}"),
+            name_pl: None,
+            parent_id,
+            archived_at: None,
+        }
+    }
+
+    fn tree() -> Tree {
+        // 1 Transport > 2 Fuel > 3 Diesel; 4 Groceries
+        Tree::new(&[
+            category(1, None),
+            category(2, Some(1)),
+            category(3, Some(2)),
+            category(4, None),
+        ])
+    }
+
+    #[test]
+    fn test_tree() {
+        let tree = tree();
+        assert_eq!(tree.path(3), [1, 2, 3]);
+        assert_eq!(tree.depth(1), 1);
+        assert_eq!(tree.at_depth(3, 1), 1);
+        assert_eq!(tree.at_depth(3, 2), 2);
+        assert_eq!(tree.at_depth(4, 2), 4);
+        assert!(tree.is_leaf(3) && tree.is_leaf(4) && !tree.is_leaf(1));
+        assert!(tree.is_descendant(3, 1));
+        assert!(!tree.is_descendant(1, 3) && !tree.is_descendant(1, 1));
+
+        // A cycle in the data must not hang
+        let cyclic = Tree::new(&[category(1, Some(2)), category(2, Some(1))]);
+        assert_eq!(cyclic.path(1), [2, 1]);
+    }
+
+    #[test]
+    fn test_roll_up() {
+        let tree = tree();
+        let totals: Totals = [
+            (Some(1), (Decimal::from(-10), 1)),
+            (Some(3), (Decimal::from(-50), 2)),
+            (Some(4), (Decimal::from(-20), 1)),
+            (None, (Decimal::from(-5), 1)),
+        ]
+        .into();
+
+        assert_eq!(roll_up(&totals, &tree, None, false), totals);
+
+        let top = roll_up(&totals, &tree, Some(1), false);
+        assert_eq!(top[&Some(1)], (Decimal::from(-60), 3));
+        assert_eq!(top.len(), 3);
+
+        let rolled = roll_up(&totals, &tree, None, true);
+        assert_eq!(rolled[&Some(1)], (Decimal::from(-60), 3));
+        assert_eq!(rolled[&Some(2)], (Decimal::from(-50), 2));
+        assert_eq!(rolled[&Some(3)], (Decimal::from(-50), 2));
+        assert_eq!(rolled[&None], (Decimal::from(-5), 1));
+
+        let two_levels = roll_up(&totals, &tree, Some(2), true);
+        assert!(!two_levels.contains_key(&Some(3)));
+        assert_eq!(two_levels[&Some(2)], (Decimal::from(-50), 2));
+    }
+}
@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use super::{Assignment, CategorizeError, Categorized, Categorizer, Item};
+use crate::services::classifier::{self, NaiveBayes};
+use crate::services::rules::Candidate;
+
+/// The locally trained classifier (`finance-tracker train`) as a categorizer.
+/// Needs no network; categories it was never trained on are never suggested.
+pub struct Classifier {
+    model: NaiveBayes,
+    names: HashMap<i32, String>,
+}
+
+impl Classifier {
+    pub fn new(model: NaiveBayes, names: HashMap<i32, String>) -> Self {
+        Self { model, names }
+    }
+}
+
+#[async_trait]
+impl Categorizer for Classifier {
+    async fn categorize(
+        &self,
+        _categories: &[String],
+        items: &[Item],
+    ) -> Result<Categorized, CategorizeError> {
+        let assignments = items
+            .iter()
+            .filter_map(|item| {
+                let candidate = Candidate {
+                    account_id: item.account_id,
+                    counterparty: item.counterparty.as_deref(),
+                    description: &item.description,
+                    amount: item.amount,
+                    bank_type: None,
+                    bank_category: None,
+                };
+                let prediction = self.model.predict(&classifier::features(&candidate))?;
+
+                Some(Assignment {
+                    index: item.index,
+                    category: self.names.get(&prediction.category_id)?.clone(),
+                    confidence: Some(prediction.confidence),
+                })
+            })
+            .collect();
+
+        Ok(Categorized {
+            assignments,
+            ..Default::default()
+        })
+    }
+
+    fn source(&self) -> &'static str {
+        classifier::SOURCE_CLASSIFIER
+    }
+}
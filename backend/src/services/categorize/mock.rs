@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+
+use super::{Assignment, CategorizeError, Categorized, Categorizer, Item};
+
+/// `category_source` of rows the mock categorises, kept apart from real
+/// model output.
+pub const SOURCE_MOCK: &str = "mock";
+
+/// Category used when no category name appears in the transaction.
+const FALLBACK: &str = "Other";
+
+/// Deterministic offline categorizer for development and tests: picks the
+/// first category whose name appears in the description or counterparty
+/// (confidence 0.9), falling back to "Other" when that category exists
+/// (confidence 0.3).
+pub struct Mock;
+
+#[async_trait]
+impl Categorizer for Mock {
+    async fn categorize(
+        &self,
+        categories: &[String],
+        items: &[Item],
+    ) -> Result<Categorized, CategorizeError> {
+        let fallback = categories.iter().find(|c| c.as_str() == FALLBACK);
+
+        let assignments = items
+            .iter()
+            .filter_map(|item| {
+                let text = format!(
+                    "{} {}",
+                    item.description,
+                    item.counterparty.as_deref().unwrap_or_default()
+                )
+                .to_lowercase();
+
+                let (category, confidence) =
+                    match categories.iter().find(|c| text.contains(&c.to_lowercase())) {
+                        Some(found) => (found, 0.9),
+                        None => (fallback?, 0.3),
+                    };
+
+                Some(Assignment {
+                    index: item.index,
+                    category: category.clone(),
+                    confidence: Some(confidence),
+                })
+            })
+            .collect();
+
+        Ok(Categorized {
+            assignments,
+            ..Default::default()
+        })
+    }
+
+    fn source(&self) -> &'static str {
+        SOURCE_MOCK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn item(index: usize, description: &str) -> Item {
+        Item {
+            index,
+            account_id: 1,
+            description: description.to_string(),
+            counterparty: None,
+            amount: Decimal::from(-10),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_is_deterministic() {
+        let categories = vec!["Fuel".to_string(), "Other".to_string()];
+        let items = vec![item(0, "ORLEN fuel station"), item(1, "Something")];
+
+        let result = Mock
+            .categorize(&categories, &items)
+            .await
+            .unwrap()
+            .assignments;
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].category, "Fuel");
+        assert_eq!(result[1].category, "Other");
+        assert!(result[0].confidence > result[1].confidence);
+
+        let without_fallback = Mock.categorize(&categories[..1], &items).await.unwrap();
+        assert_eq!(without_fallback.assignments.len(), 1);
+    }
+}
@@ -0,0 +1,545 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use sea_orm::sea_query::Query;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde::Deserialize;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::LlmConfig;
+use crate::entities::{categories, category_suggestions, transactions};
+use crate::services;
+use crate::services::categories::Tree;
+use crate::services::history::{self, Actor};
+use crate::services::jobs::{self, Progress};
+use crate::services::merchants::{self, MerchantCache};
+use crate::services::suggestions;
+
+pub mod classifier;
+pub mod mock;
+pub mod openai;
+pub mod redact;
+
+/// `category_source` of rows categorised by a language model.
+pub const SOURCE_AI: &str = "ai";
+
+/// `category_source` of rows a categorize job set: by one of the
+/// categorizer backends or from the merchant cache.
+pub const CATEGORIZER_SOURCES: [&str; 4] = [
+    SOURCE_AI,
+    services::classifier::SOURCE_CLASSIFIER,
+    mock::SOURCE_MOCK,
+    merchants::SOURCE_MERCHANT,
+];
+
+/// Every automatic `category_source`: the categorizer ones plus rules and
+/// bank category mappings. Rules may overwrite these on existing rows, and
+/// manual changes over them are recorded as overrides for the accuracy report.
+pub const AUTOMATIC_SOURCES: [&str; 6] = [
+    SOURCE_AI,
+    services::classifier::SOURCE_CLASSIFIER,
+    mock::SOURCE_MOCK,
+    merchants::SOURCE_MERCHANT,
+    services::rules::SOURCE_RULE,
+    services::bank_categories::SOURCE_BANK,
+];
+
+/// Transactions sent to the categorizer per request.
+const BATCH_SIZE: u64 = 50;
+/// Attempts per batch on transient errors (rate limits, 5xx, network).
+const MAX_ATTEMPTS: u32 = 5;
+const BACKOFF_BASE: Duration = Duration::from_secs(2);
+const BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// One transaction as shown to a categorizer; `index` is its position in the batch.
+pub struct Item {
+    pub index: usize,
+    pub account_id: i32,
+    pub description: String,
+    pub counterparty: Option<String>,
+    pub amount: Decimal,
+}
+
+#[derive(Deserialize)]
+pub struct Assignment {
+    pub index: usize,
+    pub category: String,
+    /// 0–1; a missing confidence is treated as 0 and always goes to review.
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+/// Tokens a categorizer spent on one batch.
+#[derive(Clone, Copy, Default, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: i64,
+    #[serde(default)]
+    pub completion_tokens: i64,
+}
+
+/// A categorizer's answer for one batch.
+#[derive(Default)]
+pub struct Categorized {
+    pub assignments: Vec<Assignment>,
+    pub usage: Usage,
+}
+
+#[derive(Debug)]
+pub enum CategorizeError {
+    /// Rate limited, server error or network trouble: worth retrying.
+    Transient {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// This batch's response was unusable; other batches may still work.
+    Batch(String),
+    /// Misconfiguration (bad key, unknown model, …): retrying won't help.
+    Fatal(String),
+}
+
+impl fmt::Display for CategorizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transient { message, .. } | Self::Batch(message) | Self::Fatal(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+/// A categorization backend. Given the category names and a batch of
+/// transactions it returns a category name per transaction it could place,
+/// and the tokens it used; missing or unknown categories count as failures.
+#[async_trait]
+pub trait Categorizer: Send + Sync {
+    async fn categorize(
+        &self,
+        categories: &[String],
+        items: &[Item],
+    ) -> Result<Categorized, CategorizeError>;
+
+    /// `category_source` of the rows it categorises.
+    fn source(&self) -> &'static str {
+        SOURCE_AI
+    }
+}
+
+/// Build the categorizer selected by `LLM_PROVIDER`. Model-backed providers
+/// are wrapped in PII redaction unless `LLM_REDACT` turns it off.
+pub async fn from_config(
+    db: &DatabaseConnection,
+    config: &LlmConfig,
+) -> Result<Box<dyn Categorizer>, String> {
+    match config.provider.as_str() {
+        "mock" => Ok(Box::new(mock::Mock)),
+        "classifier" => {
+            let model = services::classifier::load(db)
+                .await?
+                .ok_or("No classifier trained yet; run `finance-tracker train`")?;
+            let names = categories::Entity::find()
+                .filter(categories::Column::ArchivedAt.is_null())
+                .all(db)
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .map(|c| (c.id, c.name))
+                .collect();
+            Ok(Box::new(classifier::Classifier::new(model, names)))
+        }
+        "openai" if config.api_key.is_none() => {
+            Err("LLM_API_KEY (or OPENAI_API_KEY) not configured".into())
+        }
+        "openai" | "ollama" | "llamacpp" | "openai-compatible" => {
+            let backend = openai::OpenAiCompatible::new(config)?;
+            if config.redact {
+                Ok(Box::new(redact::Redacting::new(backend)))
+            } else {
+                Ok(Box::new(backend))
+            }
+        }
+        other => Err(format!("Unknown LLM_PROVIDER '{}'", other)),
+    }
+}
+
+enum Outcome {
+    Completed,
+    Cancelled,
+}
+
+/// Run a categorize job to the end and record how it went. Results below
+/// the auto-accept threshold become suggestions instead of categories.
+pub async fn run_job(
+    db: DatabaseConnection,
+    categorizer: Box<dyn Categorizer>,
+    config: LlmConfig,
+    job_id: i32,
+) {
+    let result = categorize_uncategorized(&db, categorizer.as_ref(), &config, job_id).await;
+    let (status, error) = match result {
+        Ok(Outcome::Completed) => (jobs::STATUS_COMPLETED, None),
+        Ok(Outcome::Cancelled) => (jobs::STATUS_CANCELLED, None),
+        Err(e) => {
+            warn!("Categorize job {} failed: {}", job_id, e);
+            (jobs::STATUS_FAILED, Some(e))
+        }
+    };
+
+    jobs::finish(&db, job_id, status, error).await;
+}
+
+/// Uncategorized transactions outside the trash without a suggestion: pending
+/// ones are waiting for review and rejected ones were already turned down.
+fn pending_categorization() -> Condition {
+    Condition::all()
+        .add(transactions::Column::CategoryId.is_null())
+        .add(transactions::Column::DeletedAt.is_null())
+        .add(
+            transactions::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(category_suggestions::Column::TransactionId)
+                    .from(category_suggestions::Entity)
+                    .to_owned(),
+            ),
+        )
+}
+
+/// Categorize every uncategorized transaction, batch by batch, reporting
+/// progress, token usage and cost on the job. Merchants already in the
+/// merchant cache get their cached category without a model call. Confident
+/// results are applied and cached, the rest are queued as suggestions (both
+/// count as succeeded). Walks the rows by id so rows that stay uncategorized
+/// are not picked up again.
+async fn categorize_uncategorized(
+    db: &DatabaseConnection,
+    categorizer: &dyn Categorizer,
+    config: &LlmConfig,
+    job_id: i32,
+) -> Result<Outcome, String> {
+    let total = transactions::Entity::find()
+        .filter(pending_categorization())
+        .count(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    jobs::start(db, job_id, total as i32).await?;
+
+    // Fetch categories
+    let cats = categories::Entity::find()
+        .filter(categories::Column::ArchivedAt.is_null())
+        .order_by_asc(categories::Column::Name)
+        .all(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Offer the most specific categories; answers naming a parent still match
+    let tree = Tree::new(&cats);
+    let category_names: Vec<String> = cats
+        .iter()
+        .filter(|c| tree.is_leaf(c.id))
+        .map(|c| c.name.clone())
+        .collect();
+    let mut cache = MerchantCache::load(db).await?;
+
+    let mut progress = Progress::default();
+    let mut suggested = 0;
+    let mut cursor = 0;
+
+    loop {
+        if jobs::cancel_requested(db, job_id).await? {
+            info!("Categorize job {} cancelled", job_id);
+            return Ok(Outcome::Cancelled);
+        }
+
+        let batch = transactions::Entity::find()
+            .filter(pending_categorization())
+            .filter(transactions::Column::Id.gt(cursor))
+            .order_by_asc(transactions::Column::Id)
+            .limit(BATCH_SIZE)
+            .all(db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let Some(last) = batch.last() else {
+            break;
+        };
+        cursor = last.id;
+
+        let fingerprints: Vec<Option<String>> = batch
+            .iter()
+            .map(|tx| merchants::fingerprint(tx.counterparty.as_deref(), &tx.description))
+            .collect();
+
+        let mut items = Vec::new();
+        for (index, (tx, fingerprint)) in batch.iter().zip(&fingerprints).enumerate() {
+            match fingerprint.as_deref().and_then(|f| cache.get(f)) {
+                Some(category_id) => {
+                    set_category(db, tx, category_id, merchants::SOURCE_MERCHANT).await?;
+                    progress.cached += 1;
+                }
+                None => items.push(Item {
+                    index,
+                    account_id: tx.account_id,
+                    description: tx.description.clone(),
+                    counterparty: tx.counterparty.clone(),
+                    amount: tx.amount,
+                }),
+            }
+        }
+
+        if items.is_empty() {
+            progress.processed += batch.len() as i32;
+            progress.succeeded += batch.len() as i32;
+            jobs::progress(db, job_id, progress).await?;
+            continue;
+        }
+
+        let cancelled = || async move {
+            jobs::cancel_requested(db, job_id)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Categorize job {}: {}", job_id, e);
+                    false
+                })
+        };
+
+        let categorized = match with_retry(categorizer, &category_names, &items, cancelled).await {
+            Ok(Some(categorized)) => categorized,
+            Ok(None) => {
+                info!("Categorize job {} cancelled", job_id);
+                return Ok(Outcome::Cancelled);
+            }
+            Err(CategorizeError::Batch(e)) => {
+                warn!("Categorize job {}: batch skipped: {}", job_id, e);
+                progress.processed += batch.len() as i32;
+                progress.succeeded += (batch.len() - items.len()) as i32;
+                progress.failed += items.len() as i32;
+                jobs::progress(db, job_id, progress).await?;
+                continue;
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+
+        progress.processed += batch.len() as i32;
+        progress.succeeded += (batch.len() - items.len()) as i32;
+        progress.prompt_tokens += categorized.usage.prompt_tokens;
+        progress.completion_tokens += categorized.usage.completion_tokens;
+        progress.estimated_cost = estimate_cost(config, &progress);
+
+        // Update transactions; only rows that were sent can be matched
+        let mut matched = vec![true; batch.len()];
+        for item in &items {
+            matched[item.index] = false;
+        }
+        for assignment in &categorized.assignments {
+            let Some(tx) = batch.get(assignment.index) else {
+                continue;
+            };
+            if matched[assignment.index] {
+                continue;
+            }
+
+            // Find category ID by name
+            let Some(cat) = cats.iter().find(|c| c.name == assignment.category) else {
+                continue;
+            };
+            matched[assignment.index] = true;
+
+            let confidence = assignment.confidence.unwrap_or(0.0).clamp(0.0, 1.0);
+            if confidence >= config.auto_accept_threshold {
+                set_category(db, tx, cat.id, categorizer.source()).await?;
+                if let Some(fingerprint) = &fingerprints[assignment.index] {
+                    merchants::remember(db, tx, cat.id).await?;
+                    cache.insert(fingerprint.clone(), cat.id);
+                }
+            } else {
+                suggestions::record(db, tx.id, cat.id, confidence, categorizer.source(), job_id)
+                    .await?;
+                suggested += 1;
+            }
+
+            progress.succeeded += 1;
+        }
+        progress.failed += matched.iter().filter(|m| !**m).count() as i32;
+
+        jobs::progress(db, job_id, progress).await?;
+    }
+
+    info!(
+        "Categorize job {} complete: {} processed, {} categorized ({} cached, {} for review), {} failed, {} tokens (~${})",
+        job_id,
+        progress.processed,
+        progress.succeeded,
+        progress.cached,
+        suggested,
+        progress.failed,
+        progress.prompt_tokens + progress.completion_tokens,
+        progress.estimated_cost
+    );
+    Ok(Outcome::Completed)
+}
+
+async fn set_category(
+    db: &DatabaseConnection,
+    tx: &transactions::Model,
+    category_id: i32,
+    source: &'static str,
+) -> Result<(), String> {
+    let mut active: transactions::ActiveModel = tx.clone().into();
+    active.category_id = Set(Some(category_id));
+    active.category_source = Set(Some(source.to_string()));
+
+    let updated = active
+        .update(db)
+        .await
+        .map_err(|e| format!("Update error: {}", e))?;
+
+    history::record_update(db, &Actor::system(source), tx, &updated).await
+}
+
+/// Cost of the tokens used so far at the configured per-million prices.
+fn estimate_cost(config: &LlmConfig, progress: &Progress) -> Decimal {
+    let million = Decimal::from(1_000_000);
+    (Decimal::from(progress.prompt_tokens) * config.input_price
+        + Decimal::from(progress.completion_tokens) * config.output_price)
+        / million
+}
+
+/// Call the categorizer, retrying transient errors with exponential backoff
+/// (or the server's `Retry-After`). Returns `None` when `cancelled` reports
+/// the job was cancelled while waiting to retry.
+async fn with_retry<F: Future<Output = bool>>(
+    categorizer: &dyn Categorizer,
+    categories: &[String],
+    items: &[Item],
+    cancelled: impl Fn() -> F,
+) -> Result<Option<Categorized>, CategorizeError> {
+    let mut attempt = 1;
+    loop {
+        match categorizer.categorize(categories, items).await {
+            Err(CategorizeError::Transient {
+                message,
+                retry_after,
+            }) if attempt < MAX_ATTEMPTS => {
+                let delay = backoff(attempt, retry_after);
+                warn!(
+                    "Categorizer attempt {}/{} failed: {}; retrying in {:?}",
+                    attempt, MAX_ATTEMPTS, message, delay
+                );
+                tokio::time::sleep(delay).await;
+                if cancelled().await {
+                    return Ok(None);
+                }
+                attempt += 1;
+            }
+            result => return result.map(Some),
+        }
+    }
+}
+
+fn backoff(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    retry_after
+        .unwrap_or_else(|| BACKOFF_BASE * 2u32.saturating_pow(attempt - 1))
+        .min(BACKOFF_MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1, None), Duration::from_secs(2));
+        assert_eq!(backoff(3, None), Duration::from_secs(8));
+        assert_eq!(backoff(10, None), BACKOFF_MAX);
+        assert_eq!(
+            backoff(1, Some(Duration::from_secs(5))),
+            Duration::from_secs(5)
+        );
+    }
+
+    /// Fails transiently a given number of times, then succeeds.
+    struct Flaky {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Categorizer for Flaky {
+        async fn categorize(
+            &self,
+            _categories: &[String],
+            _items: &[Item],
+        ) -> Result<Categorized, CategorizeError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                Err(CategorizeError::Transient {
+                    message: "429 Too Many Requests".into(),
+                    retry_after: Some(Duration::from_millis(1)),
+                })
+            } else {
+                Ok(Categorized::default())
+            }
+        }
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        let config = LlmConfig {
+            provider: "openai".into(),
+            base_url: "https://api.openai.com/v1".into(),
+            model: "gpt-4o-mini".into(),
+            api_key: None,
+            timeout: Duration::from_secs(60),
+            temperature: None,
+            auto_accept_threshold: 0.8,
+            input_price: Decimal::new(15, 2),
+            output_price: Decimal::new(60, 2),
+            redact: true,
+        };
+        let progress = Progress {
+            prompt_tokens: 2_000_000,
+            completion_tokens: 500_000,
+            ..Default::default()
+        };
+
+        assert_eq!(estimate_cost(&config, &progress), Decimal::new(60, 2));
+    }
+
+    #[tokio::test]
+    async fn test_with_retry() {
+        let flaky = Flaky {
+            failures: 2,
+            calls: AtomicU32::new(0),
+        };
+        assert!(matches!(
+            with_retry(&flaky, &[], &[], || async { false }).await,
+            Ok(Some(_))
+        ));
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+
+        let down = Flaky {
+            failures: u32::MAX,
+            calls: AtomicU32::new(0),
+        };
+        assert!(matches!(
+            with_retry(&down, &[], &[], || async { false }).await,
+            Err(CategorizeError::Transient { .. })
+        ));
+        assert_eq!(down.calls.load(Ordering::SeqCst), MAX_ATTEMPTS);
+
+        let cancelled = Flaky {
+            failures: u32::MAX,
+            calls: AtomicU32::new(0),
+        };
+        assert!(matches!(
+            with_retry(&cancelled, &[], &[], || async { true }).await,
+            Ok(None)
+        ));
+        assert_eq!(cancelled.calls.load(Ordering::SeqCst), 1);
+    }
+}
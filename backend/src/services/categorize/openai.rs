@@ -0,0 +1,220 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::info;
+
+use super::{Assignment, CategorizeError, Categorized, Categorizer, Item, Usage};
+use crate::config::LlmConfig;
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<Message>,
+    response_format: ResponseFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+    /// Some local servers leave this out.
+    #[serde(default)]
+    usage: Usage,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: MessageContent,
+}
+
+#[derive(Deserialize)]
+struct MessageContent {
+    content: String,
+}
+
+/// Any server speaking the OpenAI chat completions API: OpenAI itself, or a
+/// local Ollama / llama.cpp server so transactions never leave the machine.
+pub struct OpenAiCompatible {
+    client: Client,
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+    temperature: Option<f32>,
+}
+
+impl OpenAiCompatible {
+    pub fn new(config: &LlmConfig) -> Result<Self, String> {
+        let client = Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            endpoint: format!("{}/chat/completions", config.base_url.trim_end_matches('/')),
+            model: config.model.clone(),
+            api_key: config.api_key.clone(),
+            temperature: config.temperature,
+        })
+    }
+}
+
+#[async_trait]
+impl Categorizer for OpenAiCompatible {
+    async fn categorize(
+        &self,
+        categories: &[String],
+        items: &[Item],
+    ) -> Result<Categorized, CategorizeError> {
+        let transactions_text: String = items
+            .iter()
+            .map(|item| {
+                let cp = item.counterparty.as_deref().unwrap_or("Unknown");
+                format!(
+                    "{}: {} | {} | {}",
+                    item.index, item.description, cp, item.amount
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let system_prompt = format!(
+            "You are a financial transaction categorizer. \
+             Categorize each transaction into exactly one of these categories: {}.\n\n\
+             Respond with a JSON object containing a \"results\" key with an array of objects, \
+             each with 'index' (number), 'category' (string matching one of the categories exactly) \
+             and 'confidence' (number from 0 to 1, how sure you are).\n\
+             Example: {{\"results\": [{{\"index\": 0, \"category\": \"Groceries\", \"confidence\": 0.95}}]}}",
+            categories.join(", ")
+        );
+
+        let user_prompt = format!("Categorize these transactions:\n{}", transactions_text);
+
+        let request = ChatRequest {
+            model: &self.model,
+            messages: vec![
+                Message {
+                    role: "system".to_string(),
+                    content: system_prompt,
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: user_prompt,
+                },
+            ],
+            response_format: ResponseFormat {
+                type_: "json_object".to_string(),
+            },
+            temperature: self.temperature,
+        };
+
+        let mut builder = self.client.post(&self.endpoint).json(&request);
+        if let Some(ref key) = self.api_key {
+            builder = builder.bearer_auth(key);
+        }
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| CategorizeError::Transient {
+                message: format!("LLM request failed: {}", e),
+                retry_after: None,
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_secs);
+            let body = response.text().await.unwrap_or_default();
+            let message = format!("LLM error ({}): {}", status, body);
+
+            return Err(
+                if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                    CategorizeError::Transient {
+                        message,
+                        retry_after,
+                    }
+                } else {
+                    CategorizeError::Fatal(message)
+                },
+            );
+        }
+
+        let chat_response: ChatResponse = response
+            .json()
+            .await
+            .map_err(|e| CategorizeError::Batch(format!("Failed to parse LLM response: {}", e)))?;
+
+        let content = chat_response
+            .choices
+            .first()
+            .map(|c| c.message.content.as_str())
+            .ok_or_else(|| CategorizeError::Batch("LLM response has no choices".into()))?;
+
+        let assignments = parse_assignments(content).ok_or_else(|| {
+            info!("Failed to parse categorization response: {}", content);
+            CategorizeError::Batch("Unparseable categorization response".into())
+        })?;
+
+        Ok(Categorized {
+            assignments,
+            usage: chat_response.usage,
+        })
+    }
+}
+
+/// The response might be {"results": [...]} (or another single key) or just [...].
+fn parse_assignments(content: &str) -> Option<Vec<Assignment>> {
+    if let Ok(arr) = serde_json::from_str::<Vec<Assignment>>(content) {
+        return Some(arr);
+    }
+
+    let wrapper = serde_json::from_str::<serde_json::Value>(content).ok()?;
+    Some(
+        wrapper
+            .as_object()
+            .and_then(|o| o.values().next())
+            .and_then(|arr| serde_json::from_value(arr.clone()).ok())
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_assignments() {
+        let wrapped = parse_assignments(
+            r#"{"results": [{"index": 0, "category": "Groceries", "confidence": 0.9}]}"#,
+        )
+        .expect("wrapped array");
+        assert_eq!(wrapped.len(), 1);
+        assert_eq!(wrapped[0].category, "Groceries");
+        assert_eq!(wrapped[0].confidence, Some(0.9));
+
+        let bare = parse_assignments(r#"[{"index": 1, "category": "Fuel"}]"#).expect("bare array");
+        assert_eq!(bare[0].index, 1);
+        assert_eq!(bare[0].confidence, None);
+
+        assert!(parse_assignments("Sure! Here are the categories").is_none());
+    }
+}
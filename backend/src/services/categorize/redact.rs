@@ -0,0 +1,277 @@
+use async_trait::async_trait;
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::{CategorizeError, Categorized, Categorizer, Item};
+
+/// Patterns replaced before a transaction leaves the machine, most specific
+/// first so an IBAN is not half-eaten by the account or card pattern.
+static PATTERNS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    [
+        (r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+", "[EMAIL]"),
+        (
+            r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){3,7}(?: ?[A-Z0-9]{1,3})?\b",
+            "[IBAN]",
+        ),
+        // NRB: 26 digits, usually grouped 2 + 6×4
+        (r"\b\d{2}(?: ?\d{4}){6}\b", "[ACCOUNT]"),
+        (
+            r"\b\d{4}[ -]?[\d*xX]{4}[ -]?[\d*xX]{4}[ -]?\d{1,7}\b",
+            "[CARD]",
+        ),
+        (
+            r"(?:\+|\b00)\d{2}[ -]?\d{3}[ -]?\d{3}[ -]?\d{3}\b",
+            "[PHONE]",
+        ),
+        (r"\b[4-8]\d{2}[ -]?\d{3}[ -]?\d{3}\b", "[PHONE]"),
+        (
+            r"(?i)\b(?:ul\.|ulica|al\.|aleja|os\.|pl\.)\s*[\p{L} .-]+?\s\d+[a-z]?(?:/\d+)?",
+            "[ADDRESS]",
+        ),
+        (r"\b\d{2}-\d{3}\b", "[POSTCODE]"),
+    ]
+    .into_iter()
+    .map(|(pattern, mask)| (Regex::new(pattern).expect("valid redaction pattern"), mask))
+    .collect()
+});
+
+/// Common first names (Polish and English, lowercase). A first name followed
+/// by another word is taken as a person's name; there is no reliable way to
+/// tell "JAN NOWAK" from "SKLEP ZOO" otherwise.
+const FIRST_NAMES: [&str; 72] = [
+    "adam",
+    "agnieszka",
+    "aleksandra",
+    "andrzej",
+    "anna",
+    "barbara",
+    "bartosz",
+    "beata",
+    "dariusz",
+    "dawid",
+    "dorota",
+    "elżbieta",
+    "ewa",
+    "grzegorz",
+    "hubert",
+    "jacek",
+    "jakub",
+    "jan",
+    "janusz",
+    "jerzy",
+    "joanna",
+    "józef",
+    "julia",
+    "kamil",
+    "karolina",
+    "katarzyna",
+    "krystyna",
+    "krzysztof",
+    "łukasz",
+    "maciej",
+    "magdalena",
+    "małgorzata",
+    "marcin",
+    "marek",
+    "maria",
+    "mariusz",
+    "marta",
+    "mateusz",
+    "michał",
+    "monika",
+    "natalia",
+    "paweł",
+    "piotr",
+    "rafał",
+    "robert",
+    "ryszard",
+    "stanisław",
+    "szymon",
+    "tadeusz",
+    "tomasz",
+    "wojciech",
+    "zbigniew",
+    "zofia",
+    "david",
+    "james",
+    "jennifer",
+    "john",
+    "linda",
+    "mary",
+    "michael",
+    "patricia",
+    "richard",
+    "sarah",
+    "thomas",
+    "william",
+    "emma",
+    "oliver",
+    "sophie",
+    "daniel",
+    "laura",
+    "peter",
+    "paul",
+];
+
+const NAME: &str = "[NAME]";
+
+/// Mask account numbers, card numbers, phone numbers, emails, addresses and
+/// personal names in free text. Words of `known_names` (e.g. a counterparty
+/// recognised as a person) are masked wherever they appear.
+pub fn redact(text: &str, known_names: &[String]) -> String {
+    let mut text = text.to_string();
+    for (regex, mask) in PATTERNS.iter() {
+        text = regex.replace_all(&text, *mask).into_owned();
+    }
+
+    let words: Vec<&str> = text.split(' ').collect();
+    let mut out = Vec::with_capacity(words.len());
+    let mut i = 0;
+    while i < words.len() {
+        let word = bare(words[i]);
+        if is_first_name(&word) {
+            out.push(NAME);
+            // The surname that follows, if it looks like one
+            if words.get(i + 1).is_some_and(|next| is_name_like(next)) {
+                i += 1;
+            }
+        } else if !word.is_empty() && known_names.contains(&word) {
+            out.push(NAME);
+        } else {
+            out.push(words[i]);
+        }
+        i += 1;
+    }
+
+    let mut collapsed: Vec<&str> = Vec::with_capacity(out.len());
+    for word in out {
+        if !(word == NAME && collapsed.last() == Some(&NAME)) {
+            collapsed.push(word);
+        }
+    }
+    collapsed.join(" ")
+}
+
+/// The words of a counterparty that names a person, lowercase; empty for
+/// anything else.
+pub fn person_names(counterparty: &str) -> Vec<String> {
+    let words: Vec<String> = counterparty.split_whitespace().map(bare).collect();
+    if words.len() < 2 || words.len() > 4 || !words.iter().any(|w| is_first_name(w)) {
+        return Vec::new();
+    }
+    if !counterparty.split_whitespace().all(is_name_like) {
+        return Vec::new();
+    }
+    words
+}
+
+fn bare(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+fn is_first_name(word: &str) -> bool {
+    FIRST_NAMES.contains(&word)
+}
+
+fn is_name_like(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| matches!(c, ',' | '.' | ';' | ':'));
+    word.chars().next().is_some_and(char::is_uppercase)
+        && word.chars().all(|c| c.is_alphabetic() || c == '-')
+}
+
+/// Wraps a categorizer so it only ever sees redacted descriptions and
+/// counterparties. Items keep their batch index, so answers map straight back
+/// to the original rows.
+pub struct Redacting<C> {
+    inner: C,
+}
+
+impl<C> Redacting<C> {
+    pub fn new(inner: C) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl<C: Categorizer> Categorizer for Redacting<C> {
+    async fn categorize(
+        &self,
+        categories: &[String],
+        items: &[Item],
+    ) -> Result<Categorized, CategorizeError> {
+        let redacted: Vec<Item> = items
+            .iter()
+            .map(|item| {
+                let names = item
+                    .counterparty
+                    .as_deref()
+                    .map(person_names)
+                    .unwrap_or_default();
+                Item {
+                    index: item.index,
+                    account_id: item.account_id,
+                    description: redact(&item.description, &names),
+                    counterparty: item.counterparty.as_deref().map(|c| redact(c, &names)),
+                    amount: item.amount,
+                }
+            })
+            .collect();
+
+        self.inner.categorize(categories, &redacted).await
+    }
+
+    fn source(&self) -> &'static str {
+        self.inner.source()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_numbers_and_contacts() {
+        assert_eq!(
+            redact("Przelew z PL61 1090 1014 0000 0712 1981 2874", &[]),
+            "Przelew z [IBAN]"
+        );
+        assert_eq!(
+            redact("Na rachunek 61 1090 1014 0000 0712 1981 2874", &[]),
+            "Na rachunek [ACCOUNT]"
+        );
+        assert_eq!(
+            redact("Karta 4246 00** **** 3821 ORLEN", &[]),
+            "Karta [CARD] ORLEN"
+        );
+        assert_eq!(
+            redact("BLIK na telefon +48 600 123 456", &[]),
+            "BLIK na telefon [PHONE]"
+        );
+        assert_eq!(redact("Zwrot 600-123-456", &[]), "Zwrot [PHONE]");
+        assert_eq!(redact("Faktura jan.k@example.com", &[]), "Faktura [EMAIL]");
+        assert_eq!(
+            redact("Czynsz ul. Długa 12/4 00-950 Warszawa", &[]),
+            "Czynsz [ADDRESS] [POSTCODE] Warszawa"
+        );
+        assert_eq!(
+            redact("Zakup 12.10 kwota 45,10", &[]),
+            "Zakup 12.10 kwota 45,10"
+        );
+    }
+
+    #[test]
+    fn test_redact_names() {
+        assert_eq!(
+            redact("Przelew do: JAN NOWAK za obiad", &[]),
+            "Przelew do: [NAME] za obiad"
+        );
+
+        let names = person_names("Anna Wiśniewska");
+        assert_eq!(names, ["anna", "wiśniewska"]);
+        assert_eq!(redact("Zwrot od WIŚNIEWSKA", &names), "Zwrot od [NAME]");
+
+        assert!(person_names("Sklep Biedronka").is_empty());
+        assert!(person_names("JAN 1234").is_empty());
+    }
+}
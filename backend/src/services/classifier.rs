@@ -0,0 +1,318 @@
+use rust_decimal::prelude::ToPrimitive;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tracing::info;
+
+use crate::entities::{classifier_models, transactions};
+use crate::services::rules::{self, normalize_counterparty, Candidate};
+
+/// `category_source` recorded on rows categorised by the classifier.
+pub const SOURCE_CLASSIFIER: &str = "classifier";
+
+/// Category sources the classifier learns from: choices a person made, either
+/// directly or by writing a rule.
+const TRAINING_SOURCES: [&str; 2] = ["manual", rules::SOURCE_RULE];
+
+/// Minimum confidence for the classifier to categorise a row at import time.
+pub const IMPORT_MIN_CONFIDENCE: f32 = 0.9;
+
+const FOLDS: usize = 5;
+
+pub struct Prediction {
+    pub category_id: i32,
+    pub confidence: f32,
+}
+
+pub struct TrainResult {
+    pub model_id: i32,
+    pub samples: usize,
+    pub classes: usize,
+    /// Share of held-out rows predicted correctly in k-fold cross-validation;
+    /// `None` when there are too few rows to split.
+    pub accuracy: Option<f64>,
+}
+
+/// Multinomial naive Bayes over counterparty and description words, an amount
+/// bucket and the account, with add-one smoothing.
+#[derive(Serialize, Deserialize)]
+pub struct NaiveBayes {
+    classes: Vec<Class>,
+    vocabulary: HashSet<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Class {
+    category_id: i32,
+    documents: u32,
+    tokens: HashMap<String, u32>,
+    total: u32,
+}
+
+/// Features of a transaction: normalised counterparty and description words,
+/// the sign and order of magnitude of the amount, and the account.
+pub fn features(tx: &Candidate) -> Vec<String> {
+    let mut features = Vec::new();
+
+    if let Some(counterparty) = tx.counterparty {
+        let words = normalize_counterparty(counterparty);
+        features.extend(words.split_whitespace().map(|w| format!("c:{w}")));
+    }
+    let words = normalize_counterparty(tx.description);
+    features.extend(words.split_whitespace().map(|w| format!("d:{w}")));
+
+    let amount = tx.amount.to_f64().unwrap_or_default();
+    let sign = if amount < 0.0 { '-' } else { '+' };
+    let bucket = (amount.abs() + 1.0).log2().floor() as i32;
+    features.push(format!("a:{sign}{bucket}"));
+    features.push(format!("acct:{}", tx.account_id));
+
+    features
+}
+
+impl NaiveBayes {
+    pub fn fit(samples: &[(Vec<String>, i32)]) -> Self {
+        let mut classes: Vec<Class> = Vec::new();
+        let mut vocabulary = HashSet::new();
+
+        for (features, category_id) in samples {
+            let class = match classes.iter().position(|c| c.category_id == *category_id) {
+                Some(i) => &mut classes[i],
+                None => {
+                    classes.push(Class {
+                        category_id: *category_id,
+                        documents: 0,
+                        tokens: HashMap::new(),
+                        total: 0,
+                    });
+                    classes.last_mut().unwrap()
+                }
+            };
+
+            class.documents += 1;
+            for feature in features {
+                *class.tokens.entry(feature.clone()).or_default() += 1;
+                class.total += 1;
+                vocabulary.insert(feature.clone());
+            }
+        }
+
+        Self {
+            classes,
+            vocabulary,
+        }
+    }
+
+    /// Most likely category with its posterior probability. Features never
+    /// seen in training are ignored. There is no prediction from a model with
+    /// fewer than two classes, or for a row with no known features: the
+    /// confidence would only reflect the priors.
+    pub fn predict(&self, features: &[String]) -> Option<Prediction> {
+        if self.classes.len() < 2 {
+            return None;
+        }
+
+        let documents: u32 = self.classes.iter().map(|c| c.documents).sum();
+        let vocabulary = self.vocabulary.len() as f64;
+        let known: Vec<&String> = features
+            .iter()
+            .filter(|f| self.vocabulary.contains(*f))
+            .collect();
+        if known.is_empty() {
+            return None;
+        }
+
+        let scores: Vec<f64> = self
+            .classes
+            .iter()
+            .map(|class| {
+                let prior = (class.documents as f64 / documents as f64).ln();
+                let denominator = class.total as f64 + vocabulary;
+                known.iter().fold(prior, |score, feature| {
+                    let count = class.tokens.get(*feature).copied().unwrap_or_default();
+                    score + ((count as f64 + 1.0) / denominator).ln()
+                })
+            })
+            .collect();
+
+        let (best, max) = scores
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        let sum: f64 = scores.iter().map(|s| (s - max).exp()).sum();
+
+        Some(Prediction {
+            category_id: self.classes[best].category_id,
+            confidence: (1.0 / sum) as f32,
+        })
+    }
+
+    pub fn classes(&self) -> usize {
+        self.classes.len()
+    }
+}
+
+/// Accuracy of models trained on all folds but one, predicting the held-out
+/// fold, over every fold.
+fn cross_validate(samples: &[(Vec<String>, i32)], folds: usize) -> Option<f64> {
+    if samples.len() < folds * 2 {
+        return None;
+    }
+
+    let mut correct = 0;
+    for fold in 0..folds {
+        let (test, train): (Vec<_>, Vec<_>) = samples
+            .iter()
+            .enumerate()
+            .partition(|(i, _)| i % folds == fold);
+        let train: Vec<_> = train.into_iter().map(|(_, s)| s.clone()).collect();
+        let model = NaiveBayes::fit(&train);
+
+        correct += test
+            .iter()
+            .filter(|(_, (features, category_id))| {
+                model
+                    .predict(features)
+                    .is_some_and(|p| p.category_id == *category_id)
+            })
+            .count();
+    }
+
+    Some(correct as f64 / samples.len() as f64)
+}
+
+/// Train on every manually or rule-categorised transaction, cross-validate,
+/// and store the model as the one used from now on.
+pub async fn train(db: &DatabaseConnection) -> Result<TrainResult, String> {
+    let rows = transactions::Entity::find()
+        .filter(transactions::Column::CategoryId.is_not_null())
+        .filter(transactions::Column::CategorySource.is_in(TRAINING_SOURCES))
+        .filter(transactions::Column::DeletedAt.is_null())
+        .order_by_asc(transactions::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if rows.is_empty() {
+        return Err("No manually or rule-categorised transactions to train on".into());
+    }
+
+    let samples: Vec<(Vec<String>, i32)> = rows
+        .iter()
+        .filter_map(|tx| Some((features(&Candidate::stored(tx)), tx.category_id?)))
+        .collect();
+
+    let accuracy = cross_validate(&samples, FOLDS);
+    let model = NaiveBayes::fit(&samples);
+    let classes = model.classes();
+
+    let saved = classifier_models::ActiveModel {
+        model: Set(serde_json::to_value(&model).map_err(|e| e.to_string())?),
+        samples: Set(samples.len() as i32),
+        classes: Set(classes as i32),
+        accuracy: Set(accuracy.map(|a| a as f32)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|e| format!("Failed to save model: {}", e))?;
+
+    info!(
+        "Trained classifier {} on {} transactions in {} categories",
+        saved.id,
+        samples.len(),
+        classes
+    );
+
+    Ok(TrainResult {
+        model_id: saved.id,
+        samples: samples.len(),
+        classes,
+        accuracy,
+    })
+}
+
+/// The most recently trained model, if any.
+pub async fn load(db: &DatabaseConnection) -> Result<Option<NaiveBayes>, String> {
+    let Some(row) = classifier_models::Entity::find()
+        .order_by_desc(classifier_models::Column::Id)
+        .one(db)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+
+    serde_json::from_value(row.model)
+        .map(Some)
+        .map_err(|e| format!("Classifier model {} is unreadable: {}", row.id, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn sample(counterparty: &str, amount: i64, category_id: i32) -> (Vec<String>, i32) {
+        let tx = Candidate {
+            account_id: 1,
+            counterparty: Some(counterparty),
+            description: "Card payment",
+            amount: Decimal::from(amount),
+            bank_type: None,
+            bank_category: None,
+        };
+        (features(&tx), category_id)
+    }
+
+    #[test]
+    fn test_features() {
+        let (features, _) = sample("JMP S.A. BIEDRONKA 1234", -40, 1);
+        assert_eq!(
+            features,
+            [
+                "c:jmp",
+                "c:biedronka",
+                "d:card",
+                "d:payment",
+                "a:-5",
+                "acct:1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_predict_and_cross_validate() {
+        let mut samples = Vec::new();
+        for i in 0..10 {
+            samples.push(sample("Biedronka", -30 - i, 1));
+            samples.push(sample("Orlen Stacja", -200 - i, 2));
+        }
+
+        let model = NaiveBayes::fit(&samples);
+        let prediction = model.predict(&sample("BIEDRONKA 0815", -25, 0).0).unwrap();
+        assert_eq!(prediction.category_id, 1);
+        assert!(prediction.confidence > 0.9);
+
+        assert_eq!(cross_validate(&samples, FOLDS), Some(1.0));
+        assert_eq!(cross_validate(&samples[..4], FOLDS), None);
+    }
+
+    #[test]
+    fn test_predict_needs_evidence() {
+        let samples: Vec<_> = (0..10).map(|i| sample("Biedronka", -30 - i, 1)).collect();
+        let model = NaiveBayes::fit(&samples);
+        assert!(model.predict(&sample("Biedronka", -30, 0).0).is_none());
+
+        let mut samples = samples;
+        samples.push(sample("Orlen Stacja", -200, 2));
+        let model = NaiveBayes::fit(&samples);
+        assert!(model.predict(&sample("Biedronka", -30, 0).0).is_some());
+        assert!(model
+            .predict(&["c:zabka".to_string(), "a:9".to_string()])
+            .is_none());
+    }
+}
@@ -0,0 +1,124 @@
+use chrono::NaiveDate;
+use sea_orm::{ColumnTrait, Condition};
+use serde::Deserialize;
+
+use crate::entities::{accounts, transactions};
+use crate::services::review;
+use crate::services::tags::{self, Match as TagMatch};
+
+/// Which transactions a list or bulk edit applies to. Query conditions
+/// reference `accounts`, so it must be joined. Trashed rows never match.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct TransactionFilter {
+    pub account: Option<String>,
+    pub category_id: Option<i32>,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub search: Option<String>,
+    /// Comma-separated tag names.
+    pub tags: Option<String>,
+    /// How `tags` combine: `all` (default), `any` or `none`.
+    pub tag_match: Option<String>,
+    /// `new`, `reviewed` or `flagged`.
+    pub review_status: Option<String>,
+}
+
+impl TransactionFilter {
+    /// True if the filter would match every transaction.
+    pub fn is_empty(&self) -> bool {
+        self.account.is_none()
+            && self.category_id.is_none()
+            && self.date_from.is_none()
+            && self.date_to.is_none()
+            && self.search.is_none()
+            && self.tags.is_none()
+            && self.review_status.is_none()
+    }
+
+    pub fn condition(&self) -> Result<Condition, String> {
+        let mut condition = Condition::all().add(transactions::Column::DeletedAt.is_null());
+
+        if let Some(ref account) = self.account {
+            condition = condition.add(accounts::Column::Name.eq(account.as_str()));
+        }
+        if let Some(category_id) = self.category_id {
+            condition = condition.add(transactions::Column::CategoryId.eq(category_id));
+        }
+        if let Some(ref date_from) = self.date_from {
+            let d = parse_date("date_from", date_from)?;
+            condition = condition.add(transactions::Column::TransactionDate.gte(d));
+        }
+        if let Some(ref date_to) = self.date_to {
+            let d = parse_date("date_to", date_to)?;
+            condition = condition.add(transactions::Column::TransactionDate.lte(d));
+        }
+        if let Some(ref search) = self.search {
+            let pattern = format!("%{search}%");
+            condition = condition.add(
+                Condition::any()
+                    .add(transactions::Column::Description.like(&pattern))
+                    .add(transactions::Column::Counterparty.like(&pattern))
+                    .add(transactions::Column::Notes.like(&pattern)),
+            );
+        }
+        if let Some(ref names) = self.tags {
+            let mode = self
+                .tag_match
+                .as_deref()
+                .unwrap_or("all")
+                .parse::<TagMatch>()?;
+            let mut names = names
+                .split(',')
+                .filter(|t| !t.trim().is_empty())
+                .map(tags::normalize)
+                .collect::<Result<Vec<_>, _>>()?;
+            // `all` counts distinct matching tags, so a repeated name would never match
+            names.sort();
+            names.dedup();
+            if names.is_empty() {
+                return Err("tags must name at least one tag".into());
+            }
+            condition = condition.add(tags::condition(mode, &names));
+        }
+
+        if let Some(ref status) = self.review_status {
+            condition =
+                condition.add(transactions::Column::ReviewStatus.eq(review::parse(status)?));
+        }
+
+        Ok(condition)
+    }
+}
+
+fn parse_date(field: &str, value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("{field} must be a YYYY-MM-DD date, got '{value}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_condition_rejects_malformed() {
+        let date = TransactionFilter {
+            date_from: Some("01.10.2026".into()),
+            ..Default::default()
+        };
+        assert!(!date.is_empty());
+        assert!(date.condition().is_err());
+
+        let tags = TransactionFilter {
+            tags: Some(",".into()),
+            ..Default::default()
+        };
+        assert!(tags.condition().is_err());
+
+        let valid = TransactionFilter {
+            date_from: Some("2026-10-01".into()),
+            tags: Some("kids".into()),
+            ..Default::default()
+        };
+        assert!(valid.condition().is_ok());
+    }
+}
@@ -0,0 +1,422 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use serde_json::{json, Value};
+
+use crate::entities::{transaction_history, transaction_splits, transactions};
+use crate::services::review;
+use crate::services::splits::{self, NewSplit};
+use crate::services::tags::CHUNK;
+
+/// `changed_by` for changes made by background jobs and the CLI.
+pub const SYSTEM: &str = "system";
+
+pub const FIELD_SPLITS: &str = "splits";
+
+/// Who is making a change, and what kind of change it is.
+#[derive(Debug, Clone)]
+pub struct Actor {
+    pub user: String,
+    pub reason: &'static str,
+}
+
+impl Actor {
+    pub fn new(user: &str, reason: &'static str) -> Self {
+        Self {
+            user: user.to_string(),
+            reason,
+        }
+    }
+
+    pub fn system(reason: &'static str) -> Self {
+        Self::new(SYSTEM, reason)
+    }
+}
+
+/// A field's value before and after a change; `Null` for an empty value.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub transaction_id: i32,
+    pub field: &'static str,
+    pub old_value: Value,
+    pub new_value: Value,
+}
+
+/// The tracked fields that differ between two versions of a transaction.
+pub fn diff(before: &transactions::Model, after: &transactions::Model) -> Vec<Entry> {
+    let fields = [
+        (
+            "account_id",
+            json!(before.account_id),
+            json!(after.account_id),
+        ),
+        (
+            "transaction_date",
+            json!(before.transaction_date),
+            json!(after.transaction_date),
+        ),
+        (
+            "description",
+            json!(before.description),
+            json!(after.description),
+        ),
+        (
+            "counterparty",
+            json!(before.counterparty),
+            json!(after.counterparty),
+        ),
+        ("amount", json!(before.amount), json!(after.amount)),
+        ("currency", json!(before.currency), json!(after.currency)),
+        (
+            "category_id",
+            json!(before.category_id),
+            json!(after.category_id),
+        ),
+        (
+            "category_source",
+            json!(before.category_source),
+            json!(after.category_source),
+        ),
+        (
+            "category_rule_id",
+            json!(before.category_rule_id),
+            json!(after.category_rule_id),
+        ),
+        ("notes", json!(before.notes), json!(after.notes)),
+        ("state", json!(before.state), json!(after.state)),
+        (
+            "review_status",
+            json!(before.review_status),
+            json!(after.review_status),
+        ),
+        (
+            "deleted_at",
+            json!(before.deleted_at),
+            json!(after.deleted_at),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old_value, new_value)| Entry {
+            transaction_id: before.id,
+            field,
+            old_value,
+            new_value,
+        })
+        .collect()
+}
+
+/// How splits are stored in the history; `Null` when there are none.
+pub fn splits_value(splits: &[transaction_splits::Model]) -> Value {
+    if splits.is_empty() {
+        return Value::Null;
+    }
+    splits
+        .iter()
+        .map(|s| json!({ "category_id": s.category_id, "amount": s.amount, "note": s.note }))
+        .collect()
+}
+
+/// Store `entries`, all with the same timestamp so they read back as one change.
+pub async fn record(
+    db: &impl ConnectionTrait,
+    actor: &Actor,
+    entries: Vec<Entry>,
+) -> Result<(), String> {
+    let now = Utc::now();
+    let non_null = |v: Value| Some(v).filter(|v| !v.is_null());
+
+    let mut models = entries
+        .into_iter()
+        .map(|entry| transaction_history::ActiveModel {
+            transaction_id: Set(entry.transaction_id),
+            field: Set(entry.field.to_string()),
+            old_value: Set(non_null(entry.old_value)),
+            new_value: Set(non_null(entry.new_value)),
+            changed_by: Set(actor.user.clone()),
+            reason: Set(actor.reason.to_string()),
+            changed_at: Set(now),
+            ..Default::default()
+        })
+        .peekable();
+
+    while models.peek().is_some() {
+        let chunk: Vec<_> = models.by_ref().take(CHUNK).collect();
+        transaction_history::Entity::insert_many(chunk)
+            .exec_without_returning(db)
+            .await
+            .map_err(|e| format!("History save error: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Record the difference between `before` and `after` of one transaction.
+pub async fn record_update(
+    db: &impl ConnectionTrait,
+    actor: &Actor,
+    before: &transactions::Model,
+    after: &transactions::Model,
+) -> Result<(), String> {
+    record(db, actor, diff(before, after)).await
+}
+
+/// Record what `update` changes on each of `rows`, for bulk updates that
+/// don't return the new rows. `rows` must be read before the update.
+pub async fn record_each(
+    db: &impl ConnectionTrait,
+    actor: &Actor,
+    rows: &[transactions::Model],
+    update: impl Fn(&mut transactions::Model),
+) -> Result<(), String> {
+    let entries = rows
+        .iter()
+        .flat_map(|before| {
+            let mut after = before.clone();
+            update(&mut after);
+            diff(before, &after)
+        })
+        .collect();
+    record(db, actor, entries).await
+}
+
+/// A transaction's history, newest first.
+pub async fn for_transaction(
+    db: &DatabaseConnection,
+    transaction_id: i32,
+) -> Result<Vec<transaction_history::Model>, String> {
+    transaction_history::Entity::find()
+        .filter(transaction_history::Column::TransactionId.eq(transaction_id))
+        .order_by_desc(transaction_history::Column::ChangedAt)
+        .order_by_asc(transaction_history::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("History lookup error: {}", e))
+}
+
+/// The values a change overwrote; `None` for fields it didn't touch.
+#[derive(Debug, Default)]
+pub struct Restore {
+    pub account_id: Option<i32>,
+    pub transaction_date: Option<NaiveDate>,
+    pub description: Option<String>,
+    pub counterparty: Option<Option<String>>,
+    pub amount: Option<Decimal>,
+    pub currency: Option<String>,
+    pub category_id: Option<Option<i32>>,
+    pub category_source: Option<Option<String>>,
+    pub category_rule_id: Option<Option<i32>>,
+    pub notes: Option<Option<String>>,
+    pub state: Option<Option<String>>,
+    pub review_status: Option<String>,
+    pub deleted_at: Option<Option<DateTime<Utc>>>,
+    pub splits: Option<Vec<NewSplit>>,
+}
+
+/// Read the old values back out of the entries of one change.
+pub fn restore(entries: &[transaction_history::Model]) -> Result<Restore, String> {
+    fn old<T: serde::de::DeserializeOwned>(
+        entry: &transaction_history::Model,
+    ) -> Result<T, String> {
+        serde_json::from_value(entry.old_value.clone().unwrap_or(Value::Null))
+            .map_err(|e| format!("History entry {} has an unreadable value: {}", entry.id, e))
+    }
+
+    let mut restore = Restore::default();
+    for entry in entries {
+        match entry.field.as_str() {
+            "account_id" => restore.account_id = Some(old(entry)?),
+            "transaction_date" => restore.transaction_date = Some(old(entry)?),
+            "description" => restore.description = Some(old(entry)?),
+            "counterparty" => restore.counterparty = Some(old(entry)?),
+            "amount" => restore.amount = Some(old(entry)?),
+            "currency" => restore.currency = Some(old(entry)?),
+            "category_id" => restore.category_id = Some(old(entry)?),
+            "category_source" => restore.category_source = Some(old(entry)?),
+            "category_rule_id" => restore.category_rule_id = Some(old(entry)?),
+            "notes" => restore.notes = Some(old(entry)?),
+            "state" => restore.state = Some(old(entry)?),
+            "review_status" => restore.review_status = Some(old(entry)?),
+            "deleted_at" => restore.deleted_at = Some(old(entry)?),
+            FIELD_SPLITS => {
+                restore.splits = Some(old::<Option<Vec<NewSplit>>>(entry)?.unwrap_or_default())
+            }
+            field => return Err(format!("Cannot revert field '{field}'")),
+        }
+    }
+    Ok(restore)
+}
+
+/// Put the values of `restore` back on `tx` in one database transaction,
+/// recording the revert like any other change. Callers check that restored
+/// categories still exist and that the splits add up to the restored amount.
+pub async fn revert(
+    db: &DatabaseConnection,
+    tx: transactions::Model,
+    restore: Restore,
+    actor: &Actor,
+) -> Result<transactions::Model, String> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    let mut active: transactions::ActiveModel = tx.clone().into();
+    if let Some(account_id) = restore.account_id {
+        active.account_id.set_if_not_equals(account_id);
+    }
+    if let Some(transaction_date) = restore.transaction_date {
+        active.transaction_date.set_if_not_equals(transaction_date);
+    }
+    if let Some(description) = restore.description {
+        active.description.set_if_not_equals(description);
+    }
+    if let Some(counterparty) = restore.counterparty {
+        active.counterparty.set_if_not_equals(counterparty);
+    }
+    if let Some(amount) = restore.amount {
+        active.amount.set_if_not_equals(amount);
+    }
+    if let Some(currency) = restore.currency {
+        active.currency.set_if_not_equals(currency);
+    }
+    if let Some(category_id) = restore.category_id {
+        active.category_id.set_if_not_equals(category_id);
+    }
+    if let Some(category_source) = restore.category_source {
+        active.category_source.set_if_not_equals(category_source);
+    }
+    if let Some(category_rule_id) = restore.category_rule_id {
+        active.category_rule_id.set_if_not_equals(category_rule_id);
+    }
+    if let Some(notes) = restore.notes {
+        active.notes.set_if_not_equals(notes);
+    }
+    if let Some(state) = restore.state {
+        active.state.set_if_not_equals(state);
+    }
+    if let Some(review_status) = restore.review_status {
+        if review_status != tx.review_status {
+            active.reviewed_at = Set(review::reviewed_at(&review_status));
+            active.review_status = Set(review_status);
+        }
+    }
+    if let Some(deleted_at) = restore.deleted_at {
+        active.deleted_at.set_if_not_equals(deleted_at);
+    }
+
+    let updated = if active.is_changed() {
+        let updated = active
+            .update(&txn)
+            .await
+            .map_err(|e| format!("Update error: {}", e))?;
+        record_update(&txn, actor, &tx, &updated).await?;
+        updated
+    } else {
+        tx
+    };
+    if let Some(splits) = restore.splits {
+        splits::write(&txn, updated.id, splits, actor).await?;
+    }
+
+    txn.commit()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx() -> transactions::Model {
+        transactions::Model {
+            id: 7,
+            hash: String::new(),
+            account_id: 1,
+            transaction_date: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+            booking_date: None,
+            counterparty: None,
+            description: "ORLEN".into(),
+            amount: Decimal::from(-200),
+            currency: "PLN".into(),
+            category_id: Some(3),
+            category_source: Some("ai".into()),
+            bank_category: None,
+            bank_reference: None,
+            bank_type: None,
+            state: Some("COMPLETED".into()),
+            raw_data: None,
+            imported_at: None,
+            import_log_id: None,
+            import_row: None,
+            category_rule_id: None,
+            notes: None,
+            reviewed_at: None,
+            review_status: "new".into(),
+            source: "import".into(),
+            deleted_at: None,
+        }
+    }
+
+    fn stored(id: i32, entry: Entry) -> transaction_history::Model {
+        transaction_history::Model {
+            id,
+            transaction_id: entry.transaction_id,
+            field: entry.field.to_string(),
+            old_value: Some(entry.old_value).filter(|v| !v.is_null()),
+            new_value: Some(entry.new_value).filter(|v| !v.is_null()),
+            changed_by: "admin".into(),
+            reason: "manual".into(),
+            changed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_diff_and_restore() {
+        let before = tx();
+        let mut after = tx();
+        after.category_id = Some(4);
+        after.category_source = Some("manual".into());
+        after.notes = Some("fuel for the trip".into());
+        after.bank_type = Some("untracked".into());
+
+        let entries = diff(&before, &after);
+        let fields: Vec<_> = entries.iter().map(|e| e.field).collect();
+        assert_eq!(fields, ["category_id", "category_source", "notes"]);
+        assert!(diff(&before, &before).is_empty());
+
+        let split = |amount: i64| transaction_splits::Model {
+            id: 1,
+            transaction_id: 7,
+            category_id: Some(3),
+            amount: Decimal::from(amount),
+            note: None,
+        };
+        let splits = Entry {
+            transaction_id: 7,
+            field: FIELD_SPLITS,
+            old_value: splits_value(&[split(-150), split(-50)]),
+            new_value: splits_value(&[]),
+        };
+
+        let stored: Vec<_> = entries
+            .into_iter()
+            .chain([splits])
+            .enumerate()
+            .map(|(i, e)| stored(i as i32, e))
+            .collect();
+        let restore = restore(&stored).unwrap();
+        assert_eq!(restore.category_id, Some(Some(3)));
+        assert_eq!(restore.category_source, Some(Some("ai".into())));
+        assert_eq!(restore.notes, Some(None));
+        assert_eq!(restore.state, None);
+        let amounts: Vec<_> = restore.splits.unwrap().iter().map(|s| s.amount).collect();
+        assert_eq!(amounts, [Decimal::from(-150), Decimal::from(-50)]);
+    }
+}
@@ -1,12 +1,23 @@
 use sea_orm::{
-    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait,
-    QueryFilter, Set,
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
 };
-use tracing::info;
+use serde_json::json;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read};
+use std::path::Path;
+use tracing::{info, warn};
 
-use crate::entities::{accounts, import_logs, transactions};
+use crate::entities::{accounts, categories, import_logs, transactions};
 use crate::parsers;
 use crate::parsers::common::compute_hash;
+use crate::parsers::ParsedTransaction;
+use crate::services::bank_categories::{self, BankCategoryMap};
+use crate::services::classifier::{self, NaiveBayes};
+use crate::services::history::{self, Actor};
+use crate::services::rules::{self, Candidate, RuleSet};
+use crate::services::{archive, manual, mapping, splits};
 
 pub struct ImportResult {
     pub total_rows: i32,
@@ -14,89 +25,154 @@ pub struct ImportResult {
     pub skipped: i32,
 }
 
-pub async fn import_file(
+/// What happened to a single row; both variants carry the transaction id.
+#[derive(Clone, Copy)]
+pub enum RowOutcome {
+    Imported(i32),
+    Duplicate(i32),
+}
+
+/// Outcome of one element of a JSON bulk ingestion.
+pub struct RowResult {
+    pub index: usize,
+    pub hash: Option<String>,
+    pub outcome: Result<RowOutcome, String>,
+}
+
+impl RowResult {
+    fn failed(index: usize, error: String) -> Self {
+        Self {
+            index,
+            hash: None,
+            outcome: Err(error),
+        }
+    }
+}
+
+/// Outcome of one file within a batch upload.
+pub struct FileImport {
+    pub filename: String,
+    pub result: Result<ImportResult, String>,
+    /// The exact file was imported before; `result` holds the refusal.
+    pub duplicate: bool,
+}
+
+pub struct ReparseResult {
+    pub files: i32,
+    pub failed_files: i32,
+    pub updated: i32,
+    pub unchanged: i32,
+    /// Updated rows whose amount changed, so their splits no longer added up
+    /// and were removed.
+    pub splits_cleared: i32,
+}
+
+/// What a reparse did to one transaction.
+enum Reparsed {
+    Unchanged,
+    Updated,
+    /// Updated with a new amount; its splits were removed.
+    SplitsCleared,
+}
+
+/// The import of a file with this content, if there was one.
+async fn previous_import(
     db: &DatabaseConnection,
+    sha256: &str,
+) -> Result<Option<import_logs::Model>, String> {
+    import_logs::Entity::find()
+        .filter(import_logs::Column::ContentSha256.eq(sha256))
+        .one(db)
+        .await
+        .map_err(|e| format!("Import log lookup error: {}", e))
+}
+
+fn already_imported(previous: &import_logs::Model) -> String {
+    format!(
+        "File already imported as '{}' (import #{})",
+        previous.filename, previous.id
+    )
+}
+
+/// Import one file whose content (`sha256`) was not imported before.
+async fn import_file(
+    db: &DatabaseConnection,
+    archive_dir: &Path,
     filename: &str,
     content: &str,
+    sha256: String,
 ) -> Result<ImportResult, String> {
-    let parsed = parsers::detect_and_parse(filename, content)?;
-    let total_rows = parsed.len() as i32;
-
-    let mut imported = 0i32;
-    let mut skipped = 0i32;
-
-    for tx in &parsed {
-        let account = accounts::Entity::find()
-            .filter(accounts::Column::Name.eq(&tx.account))
-            .one(db)
-            .await
-            .map_err(|e| format!("Account '{}' lookup error: {}", tx.account, e))?
-            .ok_or_else(|| format!("Account '{}' not found", tx.account))?;
-
-        let hash = compute_hash(
-            &tx.account,
-            &tx.transaction_date.to_string(),
-            &tx.amount.to_string(),
-            &tx.description,
-        );
+    let resolver = Resolver::load(db).await?;
 
-        let model = transactions::ActiveModel {
-            hash: Set(hash),
-            account_id: Set(account.id),
-            transaction_date: Set(tx.transaction_date),
-            booking_date: Set(tx.booking_date),
-            counterparty: Set(tx.counterparty.clone()),
-            description: Set(tx.description.clone()),
-            amount: Set(tx.amount),
-            currency: Set(tx.currency.clone()),
-            category_source: Set(tx.bank_category.as_ref().map(|_| "bank".to_string())),
-            bank_category: Set(tx.bank_category.clone()),
-            bank_reference: Set(tx.bank_reference.clone()),
-            bank_type: Set(tx.bank_type.clone()),
-            state: Set(Some(tx.state.clone())),
-            raw_data: Set(Some(tx.raw_data.clone())),
-            ..Default::default()
-        };
-
-        let result = transactions::Entity::insert(model)
-            .on_conflict(
-                OnConflict::column(transactions::Column::Hash)
-                    .do_nothing()
-                    .to_owned(),
-            )
-            .exec_without_returning(db)
-            .await;
+    // The log, its rows and any accounts or mappings they need are written
+    // together: a log left behind by a failed import would refuse every retry
+    // of the file as a re-upload
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
 
-        match result {
-            Ok(_) => imported += 1,
-            Err(sea_orm::DbErr::RecordNotInserted) => skipped += 1,
-            Err(e) => return Err(format!("Insert error: {}", e)),
-        }
-    }
+    let (parsed, category_ids) = parse_and_map(&txn, filename, content).await?;
+    let total_rows = parsed.len() as i32;
+    let archive_path = archive::key_for(&sha256);
 
-    // Log the import
+    // Log the import up front so every inserted row can point back at its file
+    let mut account_ids = AccountCache::default();
     let account_name = parsed
         .first()
         .map(|t| t.account.as_str())
         .unwrap_or("unknown");
-    let account = accounts::Entity::find()
-        .filter(accounts::Column::Name.eq(account_name))
-        .one(db)
-        .await
-        .map_err(|e| format!("Account lookup error: {}", e))?
-        .ok_or_else(|| format!("Account '{}' not found", account_name))?;
+    let account_id = account_ids.resolve(&txn, account_name).await?;
 
     let log = import_logs::ActiveModel {
         filename: Set(filename.to_string()),
-        account_id: Set(account.id),
+        account_id: Set(account_id),
         total_rows: Set(total_rows),
-        imported: Set(imported),
-        skipped: Set(skipped),
+        imported: Set(0),
+        skipped: Set(0),
+        content_sha256: Set(Some(sha256.clone())),
+        archive_path: Set(Some(archive_path)),
         ..Default::default()
-    };
-    log.insert(db)
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| format!("Import log error: {}", e))?;
+
+    let mut imported = 0i32;
+    let mut skipped = 0i32;
+
+    for (row, (tx, category_id)) in parsed.iter().zip(category_ids).enumerate() {
+        let account_id = account_ids.resolve(&txn, &tx.account).await?;
+        let category = resolver.resolve(tx, account_id, category_id);
+
+        match insert_parsed(&txn, tx, account_id, category, log.id, row as i32).await? {
+            RowOutcome::Imported(_) => imported += 1,
+            RowOutcome::Duplicate(_) => skipped += 1,
+        }
+    }
+
+    let mut log: import_logs::ActiveModel = log.into();
+    log.imported = Set(imported);
+    log.skipped = Set(skipped);
+    let log = log
+        .update(&txn)
         .await
         .map_err(|e| format!("Import log error: {}", e))?;
+    txn.commit()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    // Archive the original only once the import is committed, so a rolled
+    // back import leaves no file behind. The rows are in either way; without
+    // the original the import just can't be re-parsed.
+    if let Err(e) = archive::store(archive_dir, &sha256, content.as_bytes()).await {
+        warn!("Import #{} not archived: {}", log.id, e);
+        let mut log: import_logs::ActiveModel = log.into();
+        log.archive_path = Set(None);
+        log.update(db)
+            .await
+            .map_err(|e| format!("Import log error: {}", e))?;
+    }
 
     info!(
         "Import complete: {total_rows} total, {imported} imported, {skipped} skipped (duplicates)"
@@ -108,3 +184,704 @@ pub async fn import_file(
         skipped,
     })
 }
+
+/// Ingest already-normalised rows (e.g. from scripts) through the same hashing
+/// and dedup as file imports.
+///
+/// Each element is validated on its own, so one malformed row never rejects
+/// the rest. Rows are logged in `import_logs` under `source`, one entry per
+/// account touched. The batch is written in one database transaction: a
+/// database error stores none of it.
+pub async fn import_rows(
+    db: &DatabaseConnection,
+    source: &str,
+    rows: Vec<serde_json::Value>,
+) -> Result<Vec<RowResult>, String> {
+    let resolver = Resolver::load(db).await?;
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+    let mut account_ids = AccountCache::default();
+    let mut logs: HashMap<i32, import_logs::Model> = HashMap::new();
+    let mut counts: HashMap<i32, (i32, i32, i32)> = HashMap::new();
+    let mut results = Vec::with_capacity(rows.len());
+
+    for (index, value) in rows.into_iter().enumerate() {
+        let tx: ParsedTransaction = match serde_json::from_value(value) {
+            Ok(tx) => tx,
+            Err(e) => {
+                results.push(RowResult::failed(
+                    index,
+                    format!("Invalid transaction: {e}"),
+                ));
+                continue;
+            }
+        };
+
+        let account_id = match account_ids.resolve(&txn, &tx.account).await {
+            Ok(id) => id,
+            Err(e) => {
+                results.push(RowResult::failed(index, e));
+                continue;
+            }
+        };
+
+        let log_id = match logs.entry(account_id) {
+            Entry::Occupied(entry) => entry.get().id,
+            Entry::Vacant(entry) => {
+                let log = import_logs::ActiveModel {
+                    filename: Set(source.to_string()),
+                    account_id: Set(account_id),
+                    total_rows: Set(0),
+                    imported: Set(0),
+                    skipped: Set(0),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await
+                .map_err(|e| format!("Import log error: {}", e))?;
+                entry.insert(log).id
+            }
+        };
+
+        let category = resolver.resolve(&tx, account_id, None);
+        let outcome = insert_parsed(&txn, &tx, account_id, category, log_id, index as i32).await?;
+        let entry = counts.entry(account_id).or_default();
+        entry.0 += 1;
+        match outcome {
+            RowOutcome::Imported(_) => entry.1 += 1,
+            RowOutcome::Duplicate(_) => entry.2 += 1,
+        }
+
+        results.push(RowResult {
+            index,
+            hash: Some(hash_parsed(&tx)),
+            outcome: Ok(outcome),
+        });
+    }
+
+    for (account_id, log) in logs {
+        let (total_rows, imported, skipped) = counts.get(&account_id).copied().unwrap_or_default();
+        let mut log: import_logs::ActiveModel = log.into();
+        log.total_rows = Set(total_rows);
+        log.imported = Set(imported);
+        log.skipped = Set(skipped);
+        log.update(&txn)
+            .await
+            .map_err(|e| format!("Import log error: {}", e))?;
+    }
+    txn.commit()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    Ok(results)
+}
+
+/// Import several uploaded files, expanding `.zip` archives into their entries.
+///
+/// Each file is detected and imported on its own; a failure is recorded in its
+/// result and never stops the rest of the batch.
+pub async fn import_batch(
+    db: &DatabaseConnection,
+    archive_dir: &Path,
+    files: Vec<(String, Vec<u8>)>,
+) -> Vec<FileImport> {
+    let mut results = Vec::new();
+
+    for (filename, bytes) in expand_zips(files) {
+        let mut duplicate = false;
+        let result = match bytes.and_then(|bytes| {
+            String::from_utf8(bytes).map_err(|e| format!("File is not valid UTF-8: {e}"))
+        }) {
+            Ok(content) => {
+                let sha256 = archive::sha256_hex(content.as_bytes());
+                match previous_import(db, &sha256).await {
+                    Ok(Some(previous)) => {
+                        duplicate = true;
+                        Err(already_imported(&previous))
+                    }
+                    Ok(None) => import_file(db, archive_dir, &filename, &content, sha256).await,
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        };
+
+        if let Err(ref e) = result {
+            warn!("Import of {} failed: {}", filename, e);
+        }
+        results.push(FileImport {
+            filename,
+            result,
+            duplicate,
+        });
+    }
+
+    results
+}
+
+/// Most a single file in an uploaded ZIP may decompress to.
+const ZIP_ENTRY_LIMIT: u64 = 50 * 1024 * 1024;
+/// Most all files in an uploaded ZIP may decompress to together.
+const ZIP_TOTAL_LIMIT: u64 = 200 * 1024 * 1024;
+
+/// Replace every `.zip` upload with the files it contains, named
+/// "archive.zip/entry.csv" so bank detection still sees the original name.
+/// An unreadable archive becomes a single failed entry.
+fn expand_zips(files: Vec<(String, Vec<u8>)>) -> Vec<(String, Result<Vec<u8>, String>)> {
+    let mut expanded = Vec::new();
+
+    for (filename, bytes) in files {
+        if !filename.to_lowercase().ends_with(".zip") {
+            expanded.push((filename, Ok(bytes)));
+            continue;
+        }
+
+        match read_zip(&bytes, ZIP_ENTRY_LIMIT, ZIP_TOTAL_LIMIT) {
+            Ok(entries) => expanded.extend(
+                entries
+                    .into_iter()
+                    .map(|(name, data)| (format!("{filename}/{name}"), Ok(data))),
+            ),
+            Err(e) => expanded.push((filename, Err(e))),
+        }
+    }
+
+    expanded
+}
+
+/// Extract the files of a ZIP archive. The archive is rejected as soon as a
+/// file would decompress to more than `entry_limit` bytes, or all of them to
+/// more than `total_limit`. Sizes are enforced while reading, since the
+/// sizes an archive declares can't be trusted.
+fn read_zip(
+    bytes: &[u8],
+    entry_limit: u64,
+    total_limit: u64,
+) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("Invalid ZIP archive: {e}"))?;
+
+    let mut entries = Vec::new();
+    let mut total: u64 = 0;
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| format!("Invalid ZIP entry {i}: {e}"))?;
+
+        if file.is_dir() {
+            continue;
+        }
+
+        // Skip macOS resource forks and other hidden files
+        let name = file.name().to_string();
+        let basename = name.rsplit('/').next().unwrap_or(&name);
+        if name.starts_with("__MACOSX/") || basename.starts_with('.') {
+            continue;
+        }
+
+        if file.size() > entry_limit {
+            return Err(format!(
+                "{name} is larger than {} MB uncompressed",
+                entry_limit / (1024 * 1024)
+            ));
+        }
+        let limit = entry_limit.min(total_limit - total);
+        let mut data = Vec::new();
+        (&mut file)
+            .take(limit + 1)
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Failed to extract {name}: {e}"))?;
+        if data.len() as u64 > limit {
+            return Err(if limit < entry_limit {
+                format!(
+                    "Archive is larger than {} MB uncompressed",
+                    total_limit / (1024 * 1024)
+                )
+            } else {
+                format!(
+                    "{name} is larger than {} MB uncompressed",
+                    entry_limit / (1024 * 1024)
+                )
+            });
+        }
+        total += data.len() as u64;
+        entries.push((name, data));
+    }
+
+    Ok(entries)
+}
+
+/// Re-run the current parsers over every archived original and refresh the
+/// derived fields of the transactions each file produced.
+///
+/// Rows are matched on their position in the file, so parser fixes that change
+/// the description (and therefore the dedup hash) still land on the right row.
+/// Files whose row count changed are skipped, since positions no longer line up.
+/// Category assignments are left alone, except that a bank-sourced category
+/// follows the re-parsed `bank_category`.
+pub async fn reparse_archived(
+    db: &DatabaseConnection,
+    archive_dir: &Path,
+    actor: &Actor,
+) -> Result<ReparseResult, String> {
+    let logs = import_logs::Entity::find()
+        .filter(import_logs::Column::ArchivePath.is_not_null())
+        .order_by_asc(import_logs::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("Import log lookup error: {}", e))?;
+
+    let bank_map = BankCategoryMap::load(db).await?;
+    let mut result = ReparseResult {
+        files: 0,
+        failed_files: 0,
+        updated: 0,
+        unchanged: 0,
+        splits_cleared: 0,
+    };
+
+    for log in logs {
+        result.files += 1;
+
+        let parsed = match load_and_parse(db, archive_dir, &log).await {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                warn!("Reparse of import #{} failed: {}", log.id, e);
+                result.failed_files += 1;
+                continue;
+            }
+        };
+
+        // Row positions are only meaningful while the parser yields the same rows
+        if parsed.len() as i32 != log.total_rows {
+            warn!(
+                "Reparse of import #{}: {} rows now vs {} originally, skipping",
+                log.id,
+                parsed.len(),
+                log.total_rows
+            );
+            result.failed_files += 1;
+            continue;
+        }
+
+        let mut by_row: HashMap<i32, transactions::Model> = transactions::Entity::find()
+            .filter(transactions::Column::ImportLogId.eq(log.id))
+            .all(db)
+            .await
+            .map_err(|e| format!("Transaction lookup error: {}", e))?
+            .into_iter()
+            .filter_map(|tx| tx.import_row.map(|row| (row, tx)))
+            .collect();
+
+        for (row, tx) in parsed.iter().enumerate() {
+            let Some(existing) = by_row.remove(&(row as i32)) else {
+                continue;
+            };
+
+            match apply_reparsed(db, &bank_map, existing, tx, actor).await? {
+                Reparsed::Unchanged => result.unchanged += 1,
+                Reparsed::Updated => result.updated += 1,
+                Reparsed::SplitsCleared => {
+                    result.updated += 1;
+                    result.splits_cleared += 1;
+                }
+            }
+        }
+    }
+
+    info!(
+        "Reparse complete: {} files ({} failed), {} updated ({} with splits cleared), {} unchanged",
+        result.files, result.failed_files, result.updated, result.splits_cleared, result.unchanged
+    );
+
+    Ok(result)
+}
+
+async fn load_and_parse(
+    db: &DatabaseConnection,
+    archive_dir: &Path,
+    log: &import_logs::Model,
+) -> Result<Vec<ParsedTransaction>, String> {
+    let key = log
+        .archive_path
+        .as_deref()
+        .ok_or_else(|| "No archived original".to_string())?;
+    let bytes = archive::load(archive_dir, key).await?;
+    let content =
+        String::from_utf8(bytes).map_err(|e| format!("Archived file is not valid UTF-8: {e}"))?;
+
+    // Only read the mappings: the original import already created what it needed
+    let format = parsers::detect(&log.filename, &content)?;
+    let mut parsed = format.parse(&content)?;
+    if let Some(source) = format.migration_source() {
+        mapping::resolve_accounts(db, source, &mut parsed).await?;
+    }
+    Ok(parsed)
+}
+
+/// Detect and parse a file. Exports from other finance apps are resolved onto
+/// our accounts and categories through the import mappings; the mapped
+/// category of each row is returned alongside (always `None` for bank files).
+async fn parse_and_map(
+    db: &impl ConnectionTrait,
+    filename: &str,
+    content: &str,
+) -> Result<(Vec<ParsedTransaction>, Vec<Option<i32>>), String> {
+    let format = parsers::detect(filename, content)?;
+    let mut parsed = format.parse(content)?;
+
+    let category_ids = match format.migration_source() {
+        Some(source) => mapping::apply(db, source, &mut parsed).await?,
+        None => vec![None; parsed.len()],
+    };
+
+    Ok((parsed, category_ids))
+}
+
+/// Update one transaction from its re-parsed row. Returns whether anything changed.
+async fn apply_reparsed(
+    db: &DatabaseConnection,
+    bank_map: &BankCategoryMap,
+    existing: transactions::Model,
+    tx: &ParsedTransaction,
+    actor: &Actor,
+) -> Result<Reparsed, String> {
+    let id = existing.id;
+    let mut active: transactions::ActiveModel = existing.clone().into();
+
+    // Fields the user has overridden keep their value; the re-parsed one
+    // becomes the new original
+    let mut originals = manual::originals(existing.raw_data.as_ref());
+    let mut overridden = |field: &str, value: serde_json::Value| match originals.get_mut(field) {
+        Some(original) => {
+            *original = value;
+            true
+        }
+        None => false,
+    };
+    if !overridden("transaction_date", json!(tx.transaction_date)) {
+        active
+            .transaction_date
+            .set_if_not_equals(tx.transaction_date);
+    }
+    if !overridden("counterparty", json!(tx.counterparty)) {
+        active
+            .counterparty
+            .set_if_not_equals(tx.counterparty.clone());
+    }
+    if !overridden("description", json!(tx.description)) {
+        active.description.set_if_not_equals(tx.description.clone());
+    }
+    let mut raw_data = tx.raw_data.clone();
+    manual::set_originals(&mut raw_data, originals);
+
+    active.booking_date.set_if_not_equals(tx.booking_date);
+    active.amount.set_if_not_equals(tx.amount);
+    active.currency.set_if_not_equals(tx.currency.clone());
+    active
+        .bank_category
+        .set_if_not_equals(tx.bank_category.clone());
+    active
+        .bank_reference
+        .set_if_not_equals(tx.bank_reference.clone());
+    active.bank_type.set_if_not_equals(tx.bank_type.clone());
+    active.state.set_if_not_equals(Some(tx.state.clone()));
+    active.raw_data.set_if_not_equals(Some(raw_data));
+
+    if matches!(
+        existing.category_source.as_deref(),
+        None | Some(bank_categories::SOURCE_BANK)
+    ) {
+        active
+            .category_id
+            .set_if_not_equals(bank_map.get(&tx.account, tx.bank_category.as_deref()));
+        active.category_source.set_if_not_equals(
+            tx.bank_category
+                .as_ref()
+                .map(|_| bank_categories::SOURCE_BANK.to_string()),
+        );
+    }
+
+    // Keep the dedup hash in sync with the new parse, so a later upload of the
+    // same statement is still recognised. Never steal another row's hash.
+    let hash = hash_parsed(tx);
+    if hash != existing.hash {
+        let taken = transactions::Entity::find()
+            .filter(transactions::Column::Hash.eq(&hash))
+            .one(db)
+            .await
+            .map_err(|e| format!("Hash lookup error: {}", e))?
+            .is_some();
+
+        if taken {
+            warn!("Reparse of transaction {id}: new hash collides with another row, keeping old");
+        } else {
+            active.hash = Set(hash);
+        }
+    }
+
+    if !active.is_changed() {
+        return Ok(Reparsed::Unchanged);
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+    let updated = active
+        .update(&txn)
+        .await
+        .map_err(|e| format!("Update error: {}", e))?;
+    history::record_update(&txn, actor, &existing, &updated).await?;
+
+    // Splits have to add up to the amount, so a new amount invalidates them
+    let mut outcome = Reparsed::Updated;
+    if updated.amount != existing.amount && !splits::for_transaction(&txn, id).await?.is_empty() {
+        warn!("Reparse of transaction {id}: amount changed, removing its splits");
+        splits::write(&txn, id, Vec::new(), actor).await?;
+        outcome = Reparsed::SplitsCleared;
+    }
+
+    txn.commit()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    Ok(outcome)
+}
+
+/// Account name → id lookups, memoised for the duration of one import.
+#[derive(Default)]
+struct AccountCache(HashMap<String, i32>);
+
+impl AccountCache {
+    async fn resolve(&mut self, db: &impl ConnectionTrait, name: &str) -> Result<i32, String> {
+        if let Some(id) = self.0.get(name) {
+            return Ok(*id);
+        }
+
+        let account = accounts::Entity::find()
+            .filter(accounts::Column::Name.eq(name))
+            .one(db)
+            .await
+            .map_err(|e| format!("Account '{}' lookup error: {}", name, e))?
+            .ok_or_else(|| format!("Account '{}' not found", name))?;
+
+        self.0.insert(name.to_string(), account.id);
+        Ok(account.id)
+    }
+}
+
+/// Category assigned to a row at import time.
+struct Category {
+    id: Option<i32>,
+    source: Option<String>,
+    rule_id: Option<i32>,
+}
+
+/// Everything that can categorise a row at import time.
+struct Resolver {
+    rules: RuleSet,
+    bank_map: BankCategoryMap,
+    classifier: Option<NaiveBayes>,
+    /// Categories the classifier may still pick: a saved model can predict
+    /// categories that were merged away or archived since it was trained.
+    active: HashSet<i32>,
+}
+
+impl Resolver {
+    async fn load(db: &DatabaseConnection) -> Result<Self, String> {
+        let active = categories::Entity::find()
+            .filter(categories::Column::ArchivedAt.is_null())
+            .all(db)
+            .await
+            .map_err(|e| format!("Category lookup error: {}", e))?
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+
+        Ok(Self {
+            rules: RuleSet::load(db).await?,
+            bank_map: BankCategoryMap::load(db).await?,
+            classifier: classifier::load(db).await?,
+            active,
+        })
+    }
+
+    /// A category carried over through the import mappings wins; then the
+    /// first matching rule; then the bank's own category through the bank
+    /// category mappings; then a confident classifier prediction. An unmapped
+    /// bank category only sets `category_source`.
+    fn resolve(&self, tx: &ParsedTransaction, account_id: i32, mapped: Option<i32>) -> Category {
+        if mapped.is_some() {
+            return Category {
+                id: mapped,
+                source: Some("import".to_string()),
+                rule_id: None,
+            };
+        }
+
+        let candidate = Candidate::parsed(tx, account_id);
+        if let Some(rule) = self.rules.find(&candidate) {
+            return Category {
+                id: Some(rule.category_id),
+                source: Some(rules::SOURCE_RULE.to_string()),
+                rule_id: Some(rule.id),
+            };
+        }
+
+        let bank = self.bank_map.get(&tx.account, tx.bank_category.as_deref());
+        let predicted = self
+            .classifier
+            .as_ref()
+            .and_then(|model| model.predict(&classifier::features(&candidate)))
+            .filter(|p| p.confidence >= classifier::IMPORT_MIN_CONFIDENCE)
+            .filter(|p| self.active.contains(&p.category_id));
+
+        match (bank, predicted) {
+            (None, Some(prediction)) => Category {
+                id: Some(prediction.category_id),
+                source: Some(classifier::SOURCE_CLASSIFIER.to_string()),
+                rule_id: None,
+            },
+            _ => Category {
+                id: bank,
+                source: tx
+                    .bank_category
+                    .as_ref()
+                    .map(|_| bank_categories::SOURCE_BANK.to_string()),
+                rule_id: None,
+            },
+        }
+    }
+}
+
+/// Insert one parsed row unless a transaction with the same hash exists.
+async fn insert_parsed(
+    db: &impl ConnectionTrait,
+    tx: &ParsedTransaction,
+    account_id: i32,
+    category: Category,
+    import_log_id: i32,
+    row: i32,
+) -> Result<RowOutcome, String> {
+    let hash = hash_parsed(tx);
+
+    let model = transactions::ActiveModel {
+        hash: Set(hash.clone()),
+        account_id: Set(account_id),
+        transaction_date: Set(tx.transaction_date),
+        booking_date: Set(tx.booking_date),
+        counterparty: Set(tx.counterparty.clone()),
+        description: Set(tx.description.clone()),
+        amount: Set(tx.amount),
+        currency: Set(tx.currency.clone()),
+        category_id: Set(category.id),
+        category_source: Set(category.source),
+        category_rule_id: Set(category.rule_id),
+        bank_category: Set(tx.bank_category.clone()),
+        bank_reference: Set(tx.bank_reference.clone()),
+        bank_type: Set(tx.bank_type.clone()),
+        state: Set(Some(tx.state.clone())),
+        raw_data: Set(Some(tx.raw_data.clone())),
+        import_log_id: Set(Some(import_log_id)),
+        import_row: Set(Some(row)),
+        source: Set(manual::SOURCE_IMPORT.to_string()),
+        ..Default::default()
+    };
+
+    let result = transactions::Entity::insert(model)
+        .on_conflict(
+            OnConflict::column(transactions::Column::Hash)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec(db)
+        .await;
+
+    match result {
+        Ok(res) => Ok(RowOutcome::Imported(res.last_insert_id)),
+        Err(sea_orm::DbErr::RecordNotInserted) => {
+            let existing = transactions::Entity::find()
+                .filter(transactions::Column::Hash.eq(&hash))
+                .one(db)
+                .await
+                .map_err(|e| format!("Hash lookup error: {}", e))?
+                .ok_or_else(|| format!("Transaction with hash {hash} vanished"))?;
+            Ok(RowOutcome::Duplicate(existing.id))
+        }
+        Err(e) => Err(format!("Insert error: {}", e)),
+    }
+}
+
+fn hash_parsed(tx: &ParsedTransaction) -> String {
+    compute_hash(
+        &tx.account,
+        &tx.transaction_date.to_string(),
+        &tx.amount.to_string(),
+        &tx.description,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn zip_of(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_expand_zips_names_entries_after_archive() {
+        let archive = zip_of(&[
+            ("Lista_operacji_01.csv", "a"),
+            ("__MACOSX/._Lista_operacji_01.csv", "junk"),
+            ("luty/.DS_Store", "junk"),
+            ("luty/Historia_Operacji_02.csv", "b"),
+        ]);
+
+        let expanded = expand_zips(vec![
+            ("statements.zip".into(), archive),
+            ("account-statement.csv".into(), b"c".to_vec()),
+        ]);
+
+        let names: Vec<&str> = expanded.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "statements.zip/Lista_operacji_01.csv",
+                "statements.zip/luty/Historia_Operacji_02.csv",
+                "account-statement.csv",
+            ]
+        );
+        assert_eq!(expanded[1].1.as_deref().unwrap(), b"b");
+    }
+
+    #[test]
+    fn test_read_zip_rejects_oversized_archives() {
+        let archive = zip_of(&[("a.csv", &"x".repeat(600)), ("b.csv", &"y".repeat(600))]);
+
+        assert_eq!(read_zip(&archive, 1000, 2000).unwrap().len(), 2);
+        let entry = read_zip(&archive, 500, 2000).unwrap_err();
+        assert!(entry.contains("a.csv"), "{entry}");
+        let total = read_zip(&archive, 1000, 1000).unwrap_err();
+        assert!(total.starts_with("Archive"), "{total}");
+    }
+
+    #[test]
+    fn test_expand_zips_reports_corrupt_archive() {
+        let expanded = expand_zips(vec![("broken.zip".into(), b"not a zip".to_vec())]);
+        assert_eq!(expanded.len(), 1);
+        assert!(expanded[0].1.is_err());
+    }
+}
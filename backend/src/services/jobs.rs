@@ -0,0 +1,161 @@
+use chrono::Utc;
+use rust_decimal::Decimal;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use tracing::warn;
+
+use crate::entities::jobs;
+
+pub const KIND_CATEGORIZE: &str = "categorize";
+
+pub const STATUS_QUEUED: &str = "queued";
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_COMPLETED: &str = "completed";
+pub const STATUS_FAILED: &str = "failed";
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+const ACTIVE_STATUSES: [&str; 2] = [STATUS_QUEUED, STATUS_RUNNING];
+
+#[derive(Clone, Copy, Default)]
+pub struct Progress {
+    pub processed: i32,
+    pub succeeded: i32,
+    pub failed: i32,
+    pub cached: i32,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub estimated_cost: Decimal,
+}
+
+/// The queued or running job of `kind`, if any.
+pub async fn active(db: &DatabaseConnection, kind: &str) -> Result<Option<jobs::Model>, String> {
+    jobs::Entity::find()
+        .filter(jobs::Column::Kind.eq(kind))
+        .filter(jobs::Column::Status.is_in(ACTIVE_STATUSES))
+        .order_by_desc(jobs::Column::Id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Job lookup error: {}", e))
+}
+
+pub async fn create(db: &DatabaseConnection, kind: &str) -> Result<jobs::Model, String> {
+    jobs::ActiveModel {
+        kind: Set(kind.to_string()),
+        status: Set(STATUS_QUEUED.to_string()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|e| format!("Job create error: {}", e))
+}
+
+pub async fn start(db: &DatabaseConnection, id: i32, total: i32) -> Result<(), String> {
+    jobs::Entity::update_many()
+        .col_expr(jobs::Column::Status, Expr::value(STATUS_RUNNING))
+        .col_expr(jobs::Column::Total, Expr::value(total))
+        .col_expr(jobs::Column::StartedAt, Expr::value(Utc::now()))
+        .filter(jobs::Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Job update error: {}", e))?;
+
+    Ok(())
+}
+
+pub async fn progress(db: &DatabaseConnection, id: i32, progress: Progress) -> Result<(), String> {
+    jobs::Entity::update_many()
+        .col_expr(jobs::Column::Processed, Expr::value(progress.processed))
+        .col_expr(jobs::Column::Succeeded, Expr::value(progress.succeeded))
+        .col_expr(jobs::Column::Failed, Expr::value(progress.failed))
+        .col_expr(jobs::Column::Cached, Expr::value(progress.cached))
+        .col_expr(
+            jobs::Column::PromptTokens,
+            Expr::value(progress.prompt_tokens),
+        )
+        .col_expr(
+            jobs::Column::CompletionTokens,
+            Expr::value(progress.completion_tokens),
+        )
+        .col_expr(
+            jobs::Column::EstimatedCost,
+            Expr::value(progress.estimated_cost),
+        )
+        .filter(jobs::Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Job update error: {}", e))?;
+
+    Ok(())
+}
+
+/// Record the final status. Never fails the caller: the job's own outcome
+/// matters more than bookkeeping, so errors are only logged.
+pub async fn finish(db: &DatabaseConnection, id: i32, status: &str, error: Option<String>) {
+    let result = jobs::Entity::update_many()
+        .col_expr(jobs::Column::Status, Expr::value(status))
+        .col_expr(jobs::Column::Error, Expr::value(error))
+        .col_expr(jobs::Column::FinishedAt, Expr::value(Utc::now()))
+        .filter(jobs::Column::Id.eq(id))
+        .exec(db)
+        .await;
+
+    if let Err(e) = result {
+        warn!("Failed to record the end of job {}: {}", id, e);
+    }
+}
+
+pub async fn cancel_requested(db: &DatabaseConnection, id: i32) -> Result<bool, String> {
+    let job = jobs::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Job lookup error: {}", e))?;
+
+    Ok(job.is_none_or(|j| j.cancel_requested))
+}
+
+/// Ask a queued or running job to stop; the runner picks it up between
+/// batches. Returns `None` when the job does not exist.
+pub async fn request_cancel(
+    db: &DatabaseConnection,
+    id: i32,
+) -> Result<Option<jobs::Model>, String> {
+    let Some(job) = jobs::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Job lookup error: {}", e))?
+    else {
+        return Ok(None);
+    };
+
+    if !ACTIVE_STATUSES.contains(&job.status.as_str()) {
+        return Ok(Some(job));
+    }
+
+    let mut active: jobs::ActiveModel = job.into();
+    active.cancel_requested = Set(true);
+    active
+        .update(db)
+        .await
+        .map(Some)
+        .map_err(|e| format!("Job update error: {}", e))
+}
+
+/// Jobs still marked active when the server starts were cut off by a
+/// restart; mark them failed so they don't block new ones.
+pub async fn fail_interrupted(db: &DatabaseConnection) -> Result<u64, String> {
+    let result = jobs::Entity::update_many()
+        .col_expr(jobs::Column::Status, Expr::value(STATUS_FAILED))
+        .col_expr(
+            jobs::Column::Error,
+            Expr::value("Interrupted by a server restart"),
+        )
+        .col_expr(jobs::Column::FinishedAt, Expr::value(Utc::now()))
+        .filter(jobs::Column::Status.is_in(ACTIVE_STATUSES))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Job update error: {}", e))?;
+
+    Ok(result.rows_affected)
+}
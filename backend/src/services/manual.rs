@@ -0,0 +1,261 @@
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sea_orm::Set;
+use serde_json::{json, Map, Value};
+
+use crate::entities::transactions;
+use crate::parsers::common::compute_hash;
+use crate::services::review;
+
+/// `source` of rows from statements and the JSON ingestion API.
+pub const SOURCE_IMPORT: &str = "import";
+/// `source` of rows entered by hand.
+pub const SOURCE_MANUAL: &str = "manual";
+
+/// Key in an imported row's `raw_data` holding the bank's values of the
+/// fields the user has overridden.
+pub const ORIGINAL: &str = "original";
+
+/// A transaction entered by hand.
+#[derive(Debug)]
+pub struct NewTransaction {
+    pub account_id: i32,
+    pub transaction_date: NaiveDate,
+    pub amount: Decimal,
+    pub currency: String,
+    pub description: String,
+    pub counterparty: Option<String>,
+    pub category_id: Option<i32>,
+    pub notes: Option<String>,
+}
+
+impl NewTransaction {
+    pub fn into_active_model(self, account: &str) -> transactions::ActiveModel {
+        // Two identical cash payments on one day are still two transactions,
+        // so the hash is salted with the time of entry
+        let salt = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let hash = compute_hash(
+            account,
+            &self.transaction_date.to_string(),
+            &self.amount.to_string(),
+            &format!("{} manual:{salt}", self.description),
+        );
+
+        transactions::ActiveModel {
+            hash: Set(hash),
+            account_id: Set(self.account_id),
+            transaction_date: Set(self.transaction_date),
+            description: Set(self.description),
+            counterparty: Set(self.counterparty),
+            amount: Set(self.amount),
+            currency: Set(self.currency),
+            category_source: Set(self.category_id.map(|_| "manual".to_string())),
+            category_id: Set(self.category_id),
+            notes: Set(self.notes),
+            source: Set(SOURCE_MANUAL.to_string()),
+            // Whoever typed it in has already checked it
+            review_status: Set(review::REVIEWED.to_string()),
+            reviewed_at: Set(review::reviewed_at(review::REVIEWED)),
+            ..Default::default()
+        }
+    }
+}
+
+/// Field changes to an existing transaction; `None` leaves a field alone.
+#[derive(Debug, Default)]
+pub struct Edit {
+    pub transaction_date: Option<NaiveDate>,
+    pub description: Option<String>,
+    /// `Some(None)` clears the counterparty.
+    pub counterparty: Option<Option<String>>,
+    pub amount: Option<Decimal>,
+    pub currency: Option<String>,
+    pub account_id: Option<i32>,
+}
+
+/// Apply `edit` to `tx`. Manual transactions can change freely. Imported ones
+/// keep the amount, currency and account from the bank; a changed date,
+/// description or counterparty overrides the bank's value, which is kept
+/// under `raw_data.original` (and dropped again once the override matches it).
+pub fn edit(tx: &transactions::Model, edit: Edit) -> Result<transactions::ActiveModel, String> {
+    let mut active: transactions::ActiveModel = tx.clone().into();
+
+    if tx.source != SOURCE_MANUAL {
+        let bank_owned = edit.amount.is_some_and(|a| a != tx.amount)
+            || edit.currency.as_ref().is_some_and(|c| *c != tx.currency)
+            || edit.account_id.is_some_and(|a| a != tx.account_id);
+        if bank_owned {
+            return Err(
+                "The amount, currency and account of imported transactions come from the bank"
+                    .into(),
+            );
+        }
+
+        let mut raw_data = tx.raw_data.clone().unwrap_or_else(|| json!({}));
+        if let Some(date) = edit.transaction_date {
+            keep_original(
+                &mut raw_data,
+                "transaction_date",
+                json!(tx.transaction_date),
+                json!(date),
+            );
+        }
+        if let Some(ref description) = edit.description {
+            keep_original(
+                &mut raw_data,
+                "description",
+                json!(tx.description),
+                json!(description),
+            );
+        }
+        if let Some(ref counterparty) = edit.counterparty {
+            keep_original(
+                &mut raw_data,
+                "counterparty",
+                json!(tx.counterparty),
+                json!(counterparty),
+            );
+        }
+        if tx.raw_data.is_some() || !originals(Some(&raw_data)).is_empty() {
+            active.raw_data.set_if_not_equals(Some(raw_data));
+        }
+    }
+
+    if let Some(date) = edit.transaction_date {
+        active.transaction_date.set_if_not_equals(date);
+    }
+    if let Some(description) = edit.description {
+        active.description.set_if_not_equals(description);
+    }
+    if let Some(counterparty) = edit.counterparty {
+        active.counterparty.set_if_not_equals(counterparty);
+    }
+    if let Some(amount) = edit.amount {
+        active.amount.set_if_not_equals(amount);
+    }
+    if let Some(currency) = edit.currency {
+        active.currency.set_if_not_equals(currency);
+    }
+    if let Some(account_id) = edit.account_id {
+        active.account_id.set_if_not_equals(account_id);
+    }
+    Ok(active)
+}
+
+/// The bank's values of the overridden fields of an imported row.
+pub fn originals(raw_data: Option<&Value>) -> Map<String, Value> {
+    raw_data
+        .and_then(|raw| raw.get(ORIGINAL))
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Store `originals` in `raw_data`, or remove the key when there are none.
+pub fn set_originals(raw_data: &mut Value, originals: Map<String, Value>) {
+    let Some(raw) = raw_data.as_object_mut() else {
+        return;
+    };
+    if originals.is_empty() {
+        raw.remove(ORIGINAL);
+    } else {
+        raw.insert(ORIGINAL.to_string(), Value::Object(originals));
+    }
+}
+
+fn keep_original(raw_data: &mut Value, field: &str, current: Value, new: Value) {
+    let mut originals = originals(Some(raw_data));
+    let original = originals.get(field).cloned().unwrap_or(current);
+    if new == original {
+        originals.remove(field);
+    } else {
+        originals.insert(field.to_string(), original);
+    }
+    set_originals(raw_data, originals);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn imported() -> transactions::Model {
+        transactions::Model {
+            id: 1,
+            hash: "abc".into(),
+            account_id: 1,
+            transaction_date: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+            booking_date: None,
+            counterparty: None,
+            description: "PLATNOSC KARTA 1234 ZABKA Z5512".into(),
+            amount: Decimal::from(-12),
+            currency: "PLN".into(),
+            category_id: None,
+            category_source: None,
+            bank_category: None,
+            bank_reference: None,
+            bank_type: None,
+            state: None,
+            raw_data: Some(json!({ "Kwota": "-12,00" })),
+            imported_at: None,
+            import_log_id: None,
+            import_row: None,
+            category_rule_id: None,
+            notes: None,
+            reviewed_at: None,
+            review_status: "new".into(),
+            source: SOURCE_IMPORT.into(),
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_edit_imported() {
+        let tx = imported();
+        let rename = Edit {
+            description: Some("Żabka".into()),
+            counterparty: Some(Some("Żabka".into())),
+            ..Default::default()
+        };
+        let active = edit(&tx, rename).unwrap();
+        let raw = active.raw_data.clone().unwrap().unwrap();
+        assert_eq!(raw["Kwota"], "-12,00");
+        assert_eq!(
+            raw[ORIGINAL],
+            json!({ "description": "PLATNOSC KARTA 1234 ZABKA Z5512", "counterparty": null })
+        );
+
+        // Renaming again keeps the bank's value, renaming back drops it
+        let mut renamed = tx.clone();
+        renamed.description = "Żabka".into();
+        renamed.raw_data = Some(raw);
+        let again = edit(
+            &renamed,
+            Edit {
+                description: Some("Żabka Z5512".into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let raw = again.raw_data.unwrap().unwrap();
+        assert_eq!(
+            raw[ORIGINAL]["description"],
+            "PLATNOSC KARTA 1234 ZABKA Z5512"
+        );
+        let back = edit(
+            &renamed,
+            Edit {
+                description: Some(tx.description.clone()),
+                counterparty: Some(None),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(back.raw_data.unwrap(), Some(json!({ "Kwota": "-12,00" })));
+
+        let amount = Edit {
+            amount: Some(Decimal::from(-15)),
+            ..Default::default()
+        };
+        assert!(edit(&tx, amount).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, Set, TryIntoModel,
+};
+use std::collections::HashMap;
+
+use crate::entities::{accounts, categories, import_mappings, transactions};
+use crate::parsers::ParsedTransaction;
+use crate::services::history::{self, Actor};
+
+pub const KIND_ACCOUNT: &str = "account";
+pub const KIND_CATEGORY: &str = "category";
+
+/// Resolve rows exported by another finance app onto our own accounts and
+/// categories, using (and extending) the editable `import_mappings`.
+///
+/// - Source accounts without a mapping are matched to an account of the same
+///   name, or created, and the mapping is recorded.
+/// - Source categories without a mapping are matched by name (English or
+///   Polish, whole path or last segment); the mapping is recorded even when
+///   nothing matched so it shows up for the user to fill in.
+///
+/// Rewrites `account` on every row and returns the mapped category per row.
+/// Run it in the import's database transaction, so a failed import leaves no
+/// accounts or mappings behind.
+pub async fn apply(
+    db: &impl ConnectionTrait,
+    source: &str,
+    parsed: &mut [ParsedTransaction],
+) -> Result<Vec<Option<i32>>, String> {
+    let mut mappings: HashMap<(String, String), import_mappings::Model> =
+        import_mappings::Entity::find()
+            .filter(import_mappings::Column::Source.eq(source))
+            .all(db)
+            .await
+            .map_err(|e| format!("Mapping lookup error: {}", e))?
+            .into_iter()
+            .map(|m| ((m.kind.clone(), m.source_value.clone()), m))
+            .collect();
+
+    let mut all_accounts = accounts::Entity::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Account lookup error: {}", e))?;
+    let all_categories = categories::Entity::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Category lookup error: {}", e))?;
+
+    let mut category_ids = Vec::with_capacity(parsed.len());
+
+    for tx in parsed.iter_mut() {
+        let key = (KIND_ACCOUNT.to_string(), tx.account.clone());
+        let mapped = mappings.get(&key).and_then(|m| m.account_id);
+        let account_id = match mapped {
+            Some(id) => id,
+            None => {
+                let id = match all_accounts
+                    .iter()
+                    .find(|a| a.name.eq_ignore_ascii_case(&tx.account))
+                {
+                    Some(account) => account.id,
+                    None => {
+                        let account = accounts::ActiveModel {
+                            name: Set(tx.account.clone()),
+                            currency: Set(tx.currency.clone()),
+                            ..Default::default()
+                        }
+                        .insert(db)
+                        .await
+                        .map_err(|e| format!("Account create error: {}", e))?;
+                        tracing::info!("Created account '{}' for {} import", account.name, source);
+                        let id = account.id;
+                        all_accounts.push(account);
+                        id
+                    }
+                };
+                let mapping = upsert(
+                    db,
+                    mappings.remove(&key),
+                    source,
+                    KIND_ACCOUNT,
+                    &tx.account,
+                    |m| {
+                        m.account_id = Set(Some(id));
+                    },
+                )
+                .await?;
+                mappings.insert(key, mapping);
+                id
+            }
+        };
+
+        tx.account = all_accounts
+            .iter()
+            .find(|a| a.id == account_id)
+            .map(|a| a.name.clone())
+            .ok_or_else(|| format!("Mapped account {} not found", account_id))?;
+
+        let Some(category) = tx.bank_category.clone() else {
+            category_ids.push(None);
+            continue;
+        };
+        let key = (KIND_CATEGORY.to_string(), category.clone());
+        let category_id = match mappings.get(&key) {
+            Some(m) => m.category_id,
+            None => {
+                let guess = guess_category(&all_categories, &category);
+                let mapping = upsert(db, None, source, KIND_CATEGORY, &category, |m| {
+                    m.category_id = Set(guess);
+                })
+                .await?;
+                mappings.insert(key, mapping);
+                guess
+            }
+        };
+        category_ids.push(category_id);
+    }
+
+    Ok(category_ids)
+}
+
+/// The read-only part of [`apply`] for re-parsing files imported before:
+/// rewrites `account` on every row through the existing account mappings (or
+/// an account of the same name) without creating accounts or mappings.
+pub async fn resolve_accounts(
+    db: &DatabaseConnection,
+    source: &str,
+    parsed: &mut [ParsedTransaction],
+) -> Result<(), String> {
+    let mapped: HashMap<String, i32> = import_mappings::Entity::find()
+        .filter(import_mappings::Column::Source.eq(source))
+        .filter(import_mappings::Column::Kind.eq(KIND_ACCOUNT))
+        .all(db)
+        .await
+        .map_err(|e| format!("Mapping lookup error: {}", e))?
+        .into_iter()
+        .filter_map(|m| Some((m.source_value, m.account_id?)))
+        .collect();
+    let all_accounts = accounts::Entity::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Account lookup error: {}", e))?;
+
+    for tx in parsed.iter_mut() {
+        let account = match mapped.get(&tx.account) {
+            Some(id) => all_accounts.iter().find(|a| a.id == *id),
+            None => all_accounts
+                .iter()
+                .find(|a| a.name.eq_ignore_ascii_case(&tx.account)),
+        };
+        tx.account = account
+            .map(|a| a.name.clone())
+            .ok_or_else(|| format!("No account for {} account '{}'", source, tx.account))?;
+    }
+
+    Ok(())
+}
+
+async fn upsert(
+    db: &impl ConnectionTrait,
+    existing: Option<import_mappings::Model>,
+    source: &str,
+    kind: &str,
+    value: &str,
+    set: impl FnOnce(&mut import_mappings::ActiveModel),
+) -> Result<import_mappings::Model, String> {
+    let mut active = match existing {
+        Some(m) => m.into(),
+        None => import_mappings::ActiveModel {
+            source: Set(source.to_string()),
+            kind: Set(kind.to_string()),
+            source_value: Set(value.to_string()),
+            ..Default::default()
+        },
+    };
+    set(&mut active);
+
+    active
+        .save(db)
+        .await
+        .map_err(|e| format!("Mapping save error: {}", e))?
+        .try_into_model()
+        .map_err(|e| format!("Mapping save error: {}", e))
+}
+
+/// Match another app's category name to ours, e.g. "Everyday: Groceries",
+/// "Food:Groceries" or "Artykuły spożywcze" → Groceries.
+fn guess_category(all: &[categories::Model], name: &str) -> Option<i32> {
+    let last = name.rsplit([':', '/', '>']).next().unwrap_or(name);
+
+    [name, last].iter().find_map(|candidate| {
+        let candidate = candidate.trim();
+        all.iter()
+            .find(|c| {
+                c.name.eq_ignore_ascii_case(candidate)
+                    || c.name_pl
+                        .as_deref()
+                        .is_some_and(|pl| pl.to_lowercase() == candidate.to_lowercase())
+            })
+            .map(|c| c.id)
+    })
+}
+
+/// Point an existing category mapping at a new target and re-apply it to the
+/// already imported rows it covers. Rows whose category was set by hand, and
+/// trashed rows, are left alone. Returns the number of transactions updated.
+pub async fn reapply_category(
+    db: &DatabaseConnection,
+    mapping: &import_mappings::Model,
+    actor: &Actor,
+) -> Result<u64, String> {
+    let covered = Condition::all()
+        .add(transactions::Column::BankCategory.eq(mapping.source_value.as_str()))
+        .add(Expr::cust_with_values(
+            "raw_data->>'source' = $1",
+            [mapping.source.clone()],
+        ))
+        .add(transactions::Column::DeletedAt.is_null())
+        .add(
+            Condition::any()
+                .add(transactions::Column::CategorySource.is_null())
+                .add(transactions::Column::CategorySource.is_in(["bank", "import"])),
+        );
+    let source = if mapping.category_id.is_some() {
+        "import"
+    } else {
+        "bank"
+    };
+
+    let rows = transactions::Entity::find()
+        .filter(covered.clone())
+        .all(db)
+        .await
+        .map_err(|e| format!("Transaction lookup error: {}", e))?;
+
+    let result = transactions::Entity::update_many()
+        .col_expr(
+            transactions::Column::CategoryId,
+            Expr::value(mapping.category_id),
+        )
+        .col_expr(transactions::Column::CategorySource, Expr::value(source))
+        .filter(covered)
+        .exec(db)
+        .await
+        .map_err(|e| format!("Update error: {}", e))?;
+
+    history::record_each(db, actor, &rows, |tx| {
+        tx.category_id = mapping.category_id;
+        tx.category_source = Some(source.to_string());
+    })
+    .await?;
+
+    Ok(result.rows_affected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(id: i32, name: &str, name_pl: &str) -> categories::Model {
+        categories::Model {
+            id,
+            name: name.to_string(),
+            name_pl: Some(name_pl.to_string()),
+            parent_id: None,
+            archived_at: None,
+        }
+    }
+
+    #[test]
+    fn test_guess_category() {
+        let all = vec![
+            category(1, "Groceries", "Artykuły spożywcze"),
+            category(2, "Fuel", "Paliwo"),
+        ];
+
+        assert_eq!(guess_category(&all, "groceries"), Some(1));
+        assert_eq!(guess_category(&all, "Everyday: Groceries"), Some(1));
+        assert_eq!(guess_category(&all, "Auto:Paliwo"), Some(2));
+        assert_eq!(guess_category(&all, "ARTYKUŁY SPOŻYWCZE"), Some(1));
+        assert_eq!(guess_category(&all, "Ready to Assign"), None);
+    }
+}
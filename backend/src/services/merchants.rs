@@ -0,0 +1,104 @@
+use chrono::Utc;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ConnectionTrait, DatabaseConnection, EntityTrait, Set};
+use std::collections::HashMap;
+
+use crate::entities::{merchant_categories, transactions};
+use crate::services::rules::normalize_counterparty;
+
+/// `category_source` of rows categorised from the merchant cache, without
+/// asking a categorizer.
+pub const SOURCE_MERCHANT: &str = "merchant";
+
+/// Key identifying a merchant across transactions: the normalised
+/// counterparty, or the normalised description when there is no usable
+/// counterparty. `None` when neither has any words left.
+pub fn fingerprint(counterparty: Option<&str>, description: &str) -> Option<String> {
+    counterparty
+        .map(normalize_counterparty)
+        .filter(|c| !c.is_empty())
+        .map(|c| format!("c:{c}"))
+        .or_else(|| {
+            Some(normalize_counterparty(description))
+                .filter(|d| !d.is_empty())
+                .map(|d| format!("d:{d}"))
+        })
+}
+
+/// Known merchant → category assignments, so categorize jobs only send
+/// merchants the model has not seen before.
+#[derive(Default)]
+pub struct MerchantCache(HashMap<String, i32>);
+
+impl MerchantCache {
+    pub async fn load(db: &DatabaseConnection) -> Result<Self, String> {
+        let rows = merchant_categories::Entity::find()
+            .all(db)
+            .await
+            .map_err(|e| format!("Merchant cache load error: {}", e))?;
+
+        Ok(Self(
+            rows.into_iter()
+                .map(|m| (m.fingerprint, m.category_id))
+                .collect(),
+        ))
+    }
+
+    pub fn get(&self, fingerprint: &str) -> Option<i32> {
+        self.0.get(fingerprint).copied()
+    }
+
+    pub fn insert(&mut self, fingerprint: String, category_id: i32) {
+        self.0.insert(fingerprint, category_id);
+    }
+}
+
+/// Remember the category of a transaction's merchant, replacing what was
+/// known before: the latest confident or manual decision wins.
+pub async fn remember(
+    db: &impl ConnectionTrait,
+    tx: &transactions::Model,
+    category_id: i32,
+) -> Result<(), String> {
+    let Some(fingerprint) = fingerprint(tx.counterparty.as_deref(), &tx.description) else {
+        return Ok(());
+    };
+
+    merchant_categories::Entity::insert(merchant_categories::ActiveModel {
+        fingerprint: Set(fingerprint),
+        category_id: Set(category_id),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::column(merchant_categories::Column::Fingerprint)
+            .update_columns([
+                merchant_categories::Column::CategoryId,
+                merchant_categories::Column::UpdatedAt,
+            ])
+            .to_owned(),
+    )
+    .exec(db)
+    .await
+    .map_err(|e| format!("Merchant cache save error: {}", e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            fingerprint(Some("JMP S.A. BIEDRONKA 1234"), "Zakup"),
+            fingerprint(Some("Jmp SA Biedronka 0815"), "Płatność kartą")
+        );
+        assert_eq!(
+            fingerprint(Some("1234"), "Netflix.com 12/10").as_deref(),
+            Some("d:netflix")
+        );
+        assert_eq!(fingerprint(None, "12/10 000"), None);
+    }
+}
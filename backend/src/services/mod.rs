@@ -1,2 +1,21 @@
+pub mod accuracy;
+pub mod archive;
+pub mod attachments;
+pub mod bank_categories;
+pub mod bulk_edit;
+pub mod categories;
 pub mod categorize;
+pub mod classifier;
+pub mod filter;
+pub mod history;
 pub mod import;
+pub mod jobs;
+pub mod manual;
+pub mod mapping;
+pub mod merchants;
+pub mod review;
+pub mod rules;
+pub mod splits;
+pub mod suggestions;
+pub mod tags;
+pub mod watch;
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Select};
+
+use crate::entities::transactions;
+
+/// Not yet checked; what the inbox lists.
+pub const NEW: &str = "new";
+pub const REVIEWED: &str = "reviewed";
+/// Checked and found wrong or worth a second look.
+pub const FLAGGED: &str = "flagged";
+
+pub fn parse(status: &str) -> Result<&'static str, String> {
+    [NEW, REVIEWED, FLAGGED]
+        .into_iter()
+        .find(|s| s.eq_ignore_ascii_case(status.trim()))
+        .ok_or_else(|| {
+            format!("Unknown review status '{status}'; expected new, reviewed or flagged")
+        })
+}
+
+/// `reviewed_at` for a row moving to `status`.
+pub fn reviewed_at(status: &str) -> Option<DateTime<Utc>> {
+    (status != NEW).then(Utc::now)
+}
+
+/// Transactions with `status` that aren't in the trash, oldest first so a
+/// month can be worked through in statement order.
+pub fn inbox(status: &str) -> Select<transactions::Entity> {
+    transactions::Entity::find()
+        .filter(transactions::Column::ReviewStatus.eq(status))
+        .filter(transactions::Column::DeletedAt.is_null())
+        .order_by_asc(transactions::Column::TransactionDate)
+        .order_by_asc(transactions::Column::Id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("Reviewed").unwrap(), REVIEWED);
+        assert_eq!(parse(" flagged ").unwrap(), FLAGGED);
+        assert!(parse("cleared").is_err());
+        assert!(reviewed_at(NEW).is_none());
+        assert!(reviewed_at(FLAGGED).is_some());
+    }
+}
@@ -0,0 +1,500 @@
+use regex::{Regex, RegexBuilder};
+use rust_decimal::Decimal;
+use sea_orm::sea_query::{Expr, Func};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use tracing::{info, warn};
+
+use crate::entities::{category_rules, transactions};
+use crate::parsers::ParsedTransaction;
+use crate::services::categorize::{AUTOMATIC_SOURCES, CATEGORIZER_SOURCES};
+use crate::services::history::{self, Actor};
+
+/// `category_source` recorded on rows categorised by a rule.
+pub const SOURCE_RULE: &str = "rule";
+
+const APPLY_PAGE_SIZE: u64 = 500;
+
+/// Legal forms and web noise dropped from counterparties before comparing them.
+const COUNTERPARTY_NOISE: [&str; 12] = [
+    "sp", "spj", "sa", "ska", "spolka", "spółka", "ltd", "inc", "gmbh", "llc", "www", "com",
+];
+
+pub struct ApplyResult {
+    pub checked: u64,
+    pub updated: u64,
+}
+
+/// A rule that would give a manually corrected transaction's merchant the
+/// same category from now on.
+pub struct Suggestion {
+    pub name: String,
+    pub counterparty: String,
+    pub category_id: i32,
+    pub priority: i32,
+}
+
+/// The fields of a transaction a rule can look at.
+pub struct Candidate<'a> {
+    pub account_id: i32,
+    pub counterparty: Option<&'a str>,
+    pub description: &'a str,
+    pub amount: Decimal,
+    pub bank_type: Option<&'a str>,
+    pub bank_category: Option<&'a str>,
+}
+
+impl<'a> Candidate<'a> {
+    pub fn parsed(tx: &'a ParsedTransaction, account_id: i32) -> Self {
+        Self {
+            account_id,
+            counterparty: tx.counterparty.as_deref(),
+            description: &tx.description,
+            amount: tx.amount,
+            bank_type: tx.bank_type.as_deref(),
+            bank_category: tx.bank_category.as_deref(),
+        }
+    }
+
+    pub fn stored(tx: &'a transactions::Model) -> Self {
+        Self {
+            account_id: tx.account_id,
+            counterparty: tx.counterparty.as_deref(),
+            description: &tx.description,
+            amount: tx.amount,
+            bank_type: tx.bank_type.as_deref(),
+            bank_category: tx.bank_category.as_deref(),
+        }
+    }
+}
+
+/// Reduce a counterparty to the part that identifies the merchant: lowercase
+/// words without store numbers, card fragments, dates or legal forms, so
+/// "JMP S.A. BIEDRONKA 1234" and "Jmp SA Biedronka 0815" compare equal.
+pub fn normalize_counterparty(s: &str) -> String {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 1)
+        .filter(|word| !word.chars().any(|c| c.is_ascii_digit()))
+        .filter(|word| !COUNTERPARTY_NOISE.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct CompiledRule {
+    rule: category_rules::Model,
+    /// Normalised counterparty pattern, or the raw lowercase pattern when
+    /// normalising leaves nothing (e.g. a bare number).
+    counterparty: Option<(String, bool)>,
+    description: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn matches(&self, tx: &Candidate) -> bool {
+        let rule = &self.rule;
+
+        if let Some((ref needle, normalized)) = self.counterparty {
+            let counterparty = tx.counterparty.unwrap_or_default();
+            let haystack = if normalized {
+                normalize_counterparty(counterparty)
+            } else {
+                counterparty.to_lowercase()
+            };
+            if !haystack.contains(needle.as_str()) {
+                return false;
+            }
+        }
+        if let Some(ref regex) = self.description {
+            if !regex.is_match(tx.description) {
+                return false;
+            }
+        }
+        if rule.amount_min.is_some_and(|min| tx.amount < min)
+            || rule.amount_max.is_some_and(|max| tx.amount > max)
+        {
+            return false;
+        }
+        if rule.account_id.is_some_and(|id| id != tx.account_id) {
+            return false;
+        }
+
+        equal_or_unset(rule.bank_type.as_deref(), tx.bank_type)
+            && equal_or_unset(rule.bank_category.as_deref(), tx.bank_category)
+    }
+}
+
+fn equal_or_unset(expected: Option<&str>, actual: Option<&str>) -> bool {
+    match expected {
+        None => true,
+        Some(expected) => actual.is_some_and(|a| a.to_lowercase() == expected.to_lowercase()),
+    }
+}
+
+/// Compile a rule's description pattern (case-insensitive).
+pub fn compile_regex(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("Invalid description regex: {}", e))
+}
+
+/// The enabled rules, highest priority first; ties go to the older rule.
+pub struct RuleSet(Vec<CompiledRule>);
+
+impl RuleSet {
+    pub async fn load(db: &DatabaseConnection) -> Result<Self, String> {
+        let rules = category_rules::Entity::find()
+            .filter(category_rules::Column::Enabled.eq(true))
+            .all(db)
+            .await
+            .map_err(|e| format!("Rule lookup error: {}", e))?;
+
+        Ok(Self::compile(rules))
+    }
+
+    /// Rules with a broken pattern are skipped (and logged) rather than
+    /// failing the whole import; the API refuses to save such patterns.
+    fn compile(mut rules: Vec<category_rules::Model>) -> Self {
+        rules.sort_by_key(|r| (std::cmp::Reverse(r.priority), r.id));
+
+        let compiled = rules
+            .into_iter()
+            .filter_map(|rule| {
+                let description = match rule.description_regex.as_deref() {
+                    Some(pattern) => match compile_regex(pattern) {
+                        Ok(regex) => Some(regex),
+                        Err(e) => {
+                            warn!("Skipping rule {} '{}': {}", rule.id, rule.name, e);
+                            return None;
+                        }
+                    },
+                    None => None,
+                };
+                let counterparty =
+                    rule.counterparty
+                        .as_deref()
+                        .map(|c| match normalize_counterparty(c) {
+                            n if n.is_empty() => (c.to_lowercase(), false),
+                            n => (n, true),
+                        });
+                Some(CompiledRule {
+                    counterparty,
+                    description,
+                    rule,
+                })
+            })
+            .collect();
+
+        Self(compiled)
+    }
+
+    /// The first rule matching the transaction.
+    pub fn find(&self, tx: &Candidate) -> Option<&category_rules::Model> {
+        self.0.iter().find(|c| c.matches(tx)).map(|c| &c.rule)
+    }
+}
+
+/// Suggest a rule after `tx` was categorised by hand. Returns `None` when it
+/// has no usable counterparty or an enabled rule already picks its category.
+/// The suggestion outranks any rule currently matching with another category.
+pub async fn suggest(
+    db: &DatabaseConnection,
+    tx: &transactions::Model,
+) -> Result<Option<Suggestion>, String> {
+    let Some(category_id) = tx.category_id else {
+        return Ok(None);
+    };
+    let Some(counterparty) = tx.counterparty.as_deref() else {
+        return Ok(None);
+    };
+    let normalized = normalize_counterparty(counterparty);
+    if normalized.is_empty() {
+        return Ok(None);
+    }
+
+    let rules = RuleSet::load(db).await?;
+    let priority = match rules.find(&Candidate::stored(tx)) {
+        Some(rule) if rule.category_id == category_id => return Ok(None),
+        Some(rule) => rule.priority + 1,
+        None => 0,
+    };
+
+    Ok(Some(Suggestion {
+        name: counterparty.trim().to_string(),
+        counterparty: normalized,
+        category_id,
+        priority,
+    }))
+}
+
+pub async fn create_from(
+    db: &DatabaseConnection,
+    suggestion: &Suggestion,
+) -> Result<category_rules::Model, String> {
+    let rule = category_rules::ActiveModel {
+        name: Set(suggestion.name.clone()),
+        priority: Set(suggestion.priority),
+        enabled: Set(true),
+        counterparty: Set(Some(suggestion.counterparty.clone())),
+        category_id: Set(suggestion.category_id),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|e| format!("Rule create error: {}", e))?;
+
+    info!("Learned rule {} '{}'", rule.id, rule.name);
+    Ok(rule)
+}
+
+/// Other transactions from the same merchant (equal normalised counterparty)
+/// that are still uncategorised or were only categorised by AI or the classifier.
+pub async fn find_similar(
+    db: &DatabaseConnection,
+    tx: &transactions::Model,
+) -> Result<Vec<transactions::Model>, String> {
+    let normalized = normalize_counterparty(tx.counterparty.as_deref().unwrap_or_default());
+    let Some(longest) = normalized.split(' ').max_by_key(|w| w.len()) else {
+        return Ok(Vec::new());
+    };
+    if longest.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Narrow down in SQL by the most distinctive word, compare exactly in Rust
+    let candidates = transactions::Entity::find()
+        .filter(transactions::Column::Id.ne(tx.id))
+        .filter(transactions::Column::DeletedAt.is_null())
+        .filter(
+            Condition::any()
+                .add(transactions::Column::CategoryId.is_null())
+                .add(transactions::Column::CategorySource.is_in(CATEGORIZER_SOURCES)),
+        )
+        .filter(
+            Expr::expr(Func::lower(Expr::col(transactions::Column::Counterparty)))
+                .like(format!("%{longest}%")),
+        )
+        .all(db)
+        .await
+        .map_err(|e| format!("Transaction lookup error: {}", e))?;
+
+    Ok(candidates
+        .into_iter()
+        .filter(|c| {
+            normalize_counterparty(c.counterparty.as_deref().unwrap_or_default()) == normalized
+        })
+        .collect())
+}
+
+/// Give `similar` the corrected category, attributed to `rule` when one was
+/// learned and to the user otherwise.
+pub async fn recategorize(
+    db: &DatabaseConnection,
+    similar: &[transactions::Model],
+    category_id: i32,
+    rule: Option<&category_rules::Model>,
+    actor: &Actor,
+) -> Result<u64, String> {
+    if similar.is_empty() {
+        return Ok(0);
+    }
+
+    let source = if rule.is_some() {
+        SOURCE_RULE
+    } else {
+        "manual"
+    };
+    let result = transactions::Entity::update_many()
+        .col_expr(transactions::Column::CategoryId, Expr::value(category_id))
+        .col_expr(transactions::Column::CategorySource, Expr::value(source))
+        .col_expr(
+            transactions::Column::CategoryRuleId,
+            Expr::value(rule.map(|r| r.id)),
+        )
+        .filter(transactions::Column::Id.is_in(similar.iter().map(|t| t.id)))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Update error: {}", e))?;
+
+    history::record_each(db, actor, similar, |tx| {
+        tx.category_id = Some(category_id);
+        tx.category_source = Some(source.to_string());
+        tx.category_rule_id = rule.map(|r| r.id);
+    })
+    .await?;
+
+    Ok(result.rows_affected)
+}
+
+/// Run the rules over existing transactions. Rows that were uncategorised or
+/// categorised by the bank, AI or an earlier rule are re-evaluated; rows no rule
+/// matches keep their current category. Trashed rows are left alone.
+pub async fn apply_existing(db: &DatabaseConnection, actor: &Actor) -> Result<ApplyResult, String> {
+    let rules = RuleSet::load(db).await?;
+
+    let mut pages = transactions::Entity::find()
+        .filter(transactions::Column::DeletedAt.is_null())
+        // Manual choices and categories carried over from another app are
+        // never touched
+        .filter(
+            Condition::any()
+                .add(transactions::Column::CategorySource.is_null())
+                .add(transactions::Column::CategorySource.is_in(AUTOMATIC_SOURCES)),
+        )
+        .order_by_asc(transactions::Column::Id)
+        .paginate(db, APPLY_PAGE_SIZE);
+
+    let mut checked = 0u64;
+    let mut updated = 0u64;
+
+    while let Some(page) = pages
+        .fetch_and_next()
+        .await
+        .map_err(|e| format!("Transaction lookup error: {}", e))?
+    {
+        for tx in page {
+            checked += 1;
+
+            let Some(rule) = rules.find(&Candidate::stored(&tx)) else {
+                continue;
+            };
+
+            let mut active: transactions::ActiveModel = tx.clone().into();
+            active.category_id.set_if_not_equals(Some(rule.category_id));
+            active
+                .category_source
+                .set_if_not_equals(Some(SOURCE_RULE.to_string()));
+            active.category_rule_id.set_if_not_equals(Some(rule.id));
+
+            if active.is_changed() {
+                let after = active
+                    .update(db)
+                    .await
+                    .map_err(|e| format!("Update error: {}", e))?;
+                history::record_update(db, actor, &tx, &after).await?;
+                updated += 1;
+            }
+        }
+    }
+
+    info!("Rules applied: {checked} checked, {updated} updated");
+
+    Ok(ApplyResult { checked, updated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn rule(id: i32, priority: i32, category_id: i32) -> category_rules::Model {
+        category_rules::Model {
+            id,
+            name: format!("rule {id}"),
+            priority,
+            enabled: true,
+            counterparty: None,
+            description_regex: None,
+            amount_min: None,
+            amount_max: None,
+            account_id: None,
+            bank_type: None,
+            bank_category: None,
+            category_id,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn candidate<'a>(counterparty: &'a str, description: &'a str, amount: &str) -> Candidate<'a> {
+        Candidate {
+            account_id: 1,
+            counterparty: Some(counterparty),
+            description,
+            amount: Decimal::from_str(amount).unwrap(),
+            bank_type: Some("PŁATNOŚĆ KARTĄ"),
+            bank_category: None,
+        }
+    }
+
+    #[test]
+    fn test_rule_conditions() {
+        let rules = RuleSet::compile(vec![category_rules::Model {
+            counterparty: Some("biedronka".into()),
+            description_regex: Some(r"^zakup\b".into()),
+            amount_max: Some(Decimal::ZERO),
+            bank_type: Some("płatność kartą".into()),
+            ..rule(1, 0, 10)
+        }]);
+
+        let hit = candidate(
+            "JMP S.A. BIEDRONKA 123",
+            "Zakup przy użyciu karty",
+            "-49.47",
+        );
+        assert_eq!(rules.find(&hit).map(|r| r.id), Some(1));
+
+        let refund = candidate("JMP S.A. BIEDRONKA 123", "Zakup przy użyciu karty", "49.47");
+        assert!(rules.find(&refund).is_none());
+
+        let other_shop = candidate("LIDL", "Zakup przy użyciu karty", "-49.47");
+        assert!(rules.find(&other_shop).is_none());
+
+        let scoped = RuleSet::compile(vec![category_rules::Model {
+            account_id: Some(2),
+            ..rule(2, 0, 10)
+        }]);
+        assert!(scoped.find(&hit).is_none());
+    }
+
+    #[test]
+    fn test_normalize_counterparty() {
+        assert_eq!(
+            normalize_counterparty("JMP S.A. BIEDRONKA 1234"),
+            "jmp biedronka"
+        );
+        assert_eq!(
+            normalize_counterparty("Jmp SA Biedronka 0815"),
+            "jmp biedronka"
+        );
+        assert_eq!(
+            normalize_counterparty("ŻABKA POLSKA SP. Z O.O."),
+            "żabka polska"
+        );
+        assert_eq!(normalize_counterparty("www.allegro.pl 24/02"), "allegro pl");
+        assert_eq!(normalize_counterparty("1234"), "");
+    }
+
+    #[test]
+    fn test_rule_priority_and_invalid_regex() {
+        let rules = RuleSet::compile(vec![
+            category_rules::Model {
+                description_regex: Some("(unclosed".into()),
+                ..rule(1, 100, 10)
+            },
+            category_rules::Model {
+                counterparty: Some("orlen".into()),
+                ..rule(2, 0, 20)
+            },
+            category_rules::Model {
+                counterparty: Some("orlen".into()),
+                amount_max: Some(Decimal::from(-200)),
+                ..rule(3, 5, 30)
+            },
+        ]);
+
+        assert_eq!(
+            rules
+                .find(&candidate("PKN ORLEN", "Paliwo", "-250"))
+                .map(|r| r.id),
+            Some(3)
+        );
+        assert_eq!(
+            rules
+                .find(&candidate("PKN ORLEN", "Hot dog", "-12"))
+                .map(|r| r.id),
+            Some(2)
+        );
+    }
+}
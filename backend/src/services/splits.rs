@@ -0,0 +1,169 @@
+use rust_decimal::Decimal;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+use serde::Deserialize;
+
+use crate::entities::{transaction_splits, transactions};
+use crate::services::history::{self, Actor};
+
+/// One part of a transaction as sent by the client.
+#[derive(Debug, Deserialize)]
+pub struct NewSplit {
+    pub category_id: Option<i32>,
+    pub amount: Decimal,
+    pub note: Option<String>,
+}
+
+/// Splits must cover the whole transaction: at least two parts, each non-zero
+/// with the transaction's sign, adding up to its amount exactly.
+pub fn validate(amount: Decimal, splits: &[NewSplit]) -> Result<(), String> {
+    if splits.len() < 2 {
+        return Err("A split needs at least two parts".into());
+    }
+    if let Some(split) = splits
+        .iter()
+        .find(|s| s.amount.is_zero() || s.amount.is_sign_negative() != amount.is_sign_negative())
+    {
+        return Err(format!(
+            "Split amount {} must be non-zero and have the same sign as the transaction ({})",
+            split.amount, amount
+        ));
+    }
+
+    let sum: Decimal = splits.iter().map(|s| s.amount).sum();
+    if sum != amount {
+        return Err(format!(
+            "Splits add up to {sum}, but the transaction amount is {amount}"
+        ));
+    }
+    Ok(())
+}
+
+pub async fn for_transaction(
+    db: &impl ConnectionTrait,
+    transaction_id: i32,
+) -> Result<Vec<transaction_splits::Model>, String> {
+    transaction_splits::Entity::find()
+        .filter(transaction_splits::Column::TransactionId.eq(transaction_id))
+        .order_by_asc(transaction_splits::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("Split lookup error: {}", e))
+}
+
+/// Replace all splits of `tx` in one database transaction. Callers validate
+/// the splits first.
+pub async fn replace(
+    db: &DatabaseConnection,
+    tx: &transactions::Model,
+    splits: Vec<NewSplit>,
+    actor: &Actor,
+) -> Result<Vec<transaction_splits::Model>, String> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    let splits = write(&txn, tx.id, splits, actor).await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    Ok(splits)
+}
+
+/// Remove all splits; the transaction counts under its own category again.
+pub async fn clear(
+    db: &DatabaseConnection,
+    transaction_id: i32,
+    actor: &Actor,
+) -> Result<(), String> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))?;
+
+    write(&txn, transaction_id, Vec::new(), actor).await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| format!("Transaction error: {}", e))
+}
+
+/// Swap a transaction's splits for `splits` (none to clear them) and record
+/// the change in its history. Run inside a database transaction.
+pub async fn write(
+    db: &impl ConnectionTrait,
+    transaction_id: i32,
+    splits: Vec<NewSplit>,
+    actor: &Actor,
+) -> Result<Vec<transaction_splits::Model>, String> {
+    let before = for_transaction(db, transaction_id).await?;
+
+    transaction_splits::Entity::delete_many()
+        .filter(transaction_splits::Column::TransactionId.eq(transaction_id))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Split delete error: {}", e))?;
+
+    if !splits.is_empty() {
+        let models = splits
+            .into_iter()
+            .map(|split| transaction_splits::ActiveModel {
+                transaction_id: Set(transaction_id),
+                category_id: Set(split.category_id),
+                amount: Set(split.amount),
+                note: Set(split.note.filter(|n| !n.trim().is_empty())),
+                ..Default::default()
+            });
+        transaction_splits::Entity::insert_many(models)
+            .exec(db)
+            .await
+            .map_err(|e| format!("Split save error: {}", e))?;
+    }
+
+    let after = for_transaction(db, transaction_id).await?;
+    let (old_value, new_value) = (
+        history::splits_value(&before),
+        history::splits_value(&after),
+    );
+    if old_value != new_value {
+        let entry = history::Entry {
+            transaction_id,
+            field: history::FIELD_SPLITS,
+            old_value,
+            new_value,
+        };
+        history::record(db, actor, vec![entry]).await?;
+    }
+
+    Ok(after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn split(amount: &str) -> NewSplit {
+        NewSplit {
+            category_id: Some(1),
+            amount: Decimal::from_str(amount).unwrap(),
+            note: None,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let amount = Decimal::from_str("-123.45").unwrap();
+
+        assert!(validate(amount, &[split("-100.00"), split("-23.45")]).is_ok());
+        assert!(validate(amount, &[split("-123.45")]).is_err());
+        assert!(validate(amount, &[split("-100.00"), split("-23.44")]).is_err());
+        assert!(validate(amount, &[split("-133.45"), split("10.00")]).is_err());
+        assert!(validate(amount, &[split("-123.45"), split("0")]).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use chrono::Utc;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+
+use crate::entities::{category_suggestions, transactions};
+use crate::services::history::{self, Actor};
+use crate::services::{accuracy, merchants};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_ACCEPTED: &str = "accepted";
+pub const STATUS_EDITED: &str = "edited";
+pub const STATUS_REJECTED: &str = "rejected";
+/// Closed without applying because the transaction was categorised since.
+pub const STATUS_SUPERSEDED: &str = "superseded";
+
+/// Decisions on pending suggestions, by suggestion id.
+#[derive(Default)]
+pub struct Review {
+    pub accept: Vec<i32>,
+    pub reject: Vec<i32>,
+    /// (suggestion id, category id the user picked instead)
+    pub edit: Vec<(i32, i32)>,
+}
+
+pub struct ReviewResult {
+    pub accepted: u64,
+    pub edited: u64,
+    pub rejected: u64,
+    /// Ids that were unknown or already reviewed, or whose transaction was
+    /// categorised or trashed since the suggestion was made.
+    pub skipped: Vec<i32>,
+}
+
+/// Queue a low-confidence categorization for review. `source` is the
+/// categorizer that made it, applied as the category source on accept.
+pub async fn record(
+    db: &DatabaseConnection,
+    transaction_id: i32,
+    category_id: i32,
+    confidence: f32,
+    source: &str,
+    job_id: i32,
+) -> Result<(), String> {
+    category_suggestions::Entity::insert(category_suggestions::ActiveModel {
+        transaction_id: Set(transaction_id),
+        category_id: Set(category_id),
+        confidence: Set(confidence),
+        source: Set(source.to_string()),
+        status: Set(STATUS_PENDING.to_string()),
+        job_id: Set(Some(job_id)),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::column(category_suggestions::Column::TransactionId)
+            .update_columns([
+                category_suggestions::Column::CategoryId,
+                category_suggestions::Column::Confidence,
+                category_suggestions::Column::Source,
+                category_suggestions::Column::Status,
+                category_suggestions::Column::JobId,
+            ])
+            .to_owned(),
+    )
+    .exec(db)
+    .await
+    .map_err(|e| format!("Suggestion save error: {}", e))?;
+
+    Ok(())
+}
+
+/// Apply review decisions, each in its own database transaction. Accepted
+/// suggestions set the transaction's category with the source of the
+/// categorizer that made them; edited ones set the user's pick as a manual
+/// category; both teach the merchant cache. Rejected ones leave the
+/// transaction uncategorized and keep it out of later categorize jobs.
+/// Suggestions are only made for uncategorised rows, so one whose
+/// transaction has since been given a category (or a manual or rule source)
+/// is not applied; it is closed as superseded and reported as skipped.
+pub async fn review(
+    db: &DatabaseConnection,
+    review: Review,
+    actor: &Actor,
+) -> Result<ReviewResult, String> {
+    let mut result = ReviewResult {
+        accepted: 0,
+        edited: 0,
+        rejected: 0,
+        skipped: Vec::new(),
+    };
+
+    let decisions = review
+        .accept
+        .into_iter()
+        .map(|id| (id, STATUS_ACCEPTED, None))
+        .chain(
+            review
+                .edit
+                .into_iter()
+                .map(|(id, category_id)| (id, STATUS_EDITED, Some(category_id))),
+        )
+        .chain(
+            review
+                .reject
+                .into_iter()
+                .map(|id| (id, STATUS_REJECTED, None)),
+        );
+
+    for (id, status, edited_category) in decisions {
+        let txn = db
+            .begin()
+            .await
+            .map_err(|e| format!("Transaction error: {}", e))?;
+
+        let applied = decide(&txn, id, status, edited_category, actor).await?;
+
+        txn.commit()
+            .await
+            .map_err(|e| format!("Transaction error: {}", e))?;
+
+        match (applied, status) {
+            (false, _) => result.skipped.push(id),
+            (true, STATUS_ACCEPTED) => result.accepted += 1,
+            (true, STATUS_EDITED) => result.edited += 1,
+            (true, _) => result.rejected += 1,
+        }
+    }
+
+    Ok(result)
+}
+
+/// Apply one review decision. Returns false when the suggestion was skipped.
+async fn decide(
+    db: &impl ConnectionTrait,
+    id: i32,
+    status: &str,
+    edited_category: Option<i32>,
+    actor: &Actor,
+) -> Result<bool, String> {
+    let suggestion = category_suggestions::Entity::find_by_id(id)
+        .filter(category_suggestions::Column::Status.eq(STATUS_PENDING))
+        .one(db)
+        .await
+        .map_err(|e| format!("Suggestion lookup error: {}", e))?;
+    let Some(suggestion) = suggestion else {
+        return Ok(false);
+    };
+
+    let category_id = edited_category.unwrap_or(suggestion.category_id);
+
+    if status != STATUS_REJECTED {
+        let source = if status == STATUS_EDITED {
+            "manual"
+        } else {
+            suggestion.source.as_str()
+        };
+        let tx = transactions::Entity::find_by_id(suggestion.transaction_id)
+            .one(db)
+            .await
+            .map_err(|e| format!("Transaction lookup error: {}", e))?;
+        let Some(mut tx) = tx.filter(still_open) else {
+            close(db, id, STATUS_SUPERSEDED, suggestion.category_id).await?;
+            return Ok(false);
+        };
+
+        transactions::Entity::update_many()
+            .col_expr(transactions::Column::CategoryId, Expr::value(category_id))
+            .col_expr(transactions::Column::CategorySource, Expr::value(source))
+            .col_expr(
+                transactions::Column::CategoryRuleId,
+                Expr::value(Option::<i32>::None),
+            )
+            .filter(transactions::Column::Id.eq(suggestion.transaction_id))
+            .exec(db)
+            .await
+            .map_err(|e| format!("Update error: {}", e))?;
+
+        let before = tx.clone();
+        tx.category_id = Some(category_id);
+        tx.category_source = Some(source.to_string());
+        tx.category_rule_id = None;
+        history::record_update(db, actor, &before, &tx).await?;
+        merchants::remember(db, &tx, category_id).await?;
+        if status == STATUS_EDITED {
+            accuracy::record_suggestion_edit(
+                db,
+                suggestion.transaction_id,
+                suggestion.category_id,
+                &suggestion.source,
+                category_id,
+            )
+            .await?;
+        }
+    }
+
+    close(db, id, status, category_id).await?;
+
+    Ok(true)
+}
+
+/// Whether a suggestion for `tx` can still be applied: nothing has
+/// categorised the transaction since, and it isn't in the trash.
+fn still_open(tx: &transactions::Model) -> bool {
+    tx.category_id.is_none()
+        && !matches!(tx.category_source.as_deref(), Some("manual" | "rule"))
+        && tx.deleted_at.is_none()
+}
+
+async fn close(
+    db: &impl ConnectionTrait,
+    id: i32,
+    status: &str,
+    category_id: i32,
+) -> Result<(), String> {
+    category_suggestions::Entity::update_many()
+        .col_expr(category_suggestions::Column::Status, Expr::value(status))
+        .col_expr(
+            category_suggestions::Column::CategoryId,
+            Expr::value(category_id),
+        )
+        .col_expr(
+            category_suggestions::Column::ReviewedAt,
+            Expr::value(Utc::now()),
+        )
+        .filter(category_suggestions::Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Suggestion update error: {}", e))?;
+    Ok(())
+}
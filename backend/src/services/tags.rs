@@ -0,0 +1,242 @@
+use sea_orm::sea_query::{Expr, OnConflict, SimpleExpr};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, JoinType, QueryFilter, QueryOrder, QuerySelect,
+    RelationTrait, Set, Value,
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::entities::{tags, transaction_tags};
+
+pub const MAX_LEN: usize = 64;
+
+/// Transaction ids per statement, well below Postgres' bind parameter limit.
+pub const CHUNK: usize = 5000;
+
+/// How a tag filter combines several tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Match {
+    /// Transactions carrying every tag.
+    All,
+    /// Transactions carrying at least one of the tags.
+    Any,
+    /// Transactions carrying none of the tags.
+    None,
+}
+
+impl FromStr for Match {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Match::All),
+            "any" => Ok(Match::Any),
+            "none" => Ok(Match::None),
+            other => Err(format!(
+                "Unknown tag match '{other}' (expected all, any or none)"
+            )),
+        }
+    }
+}
+
+/// Tags are lowercase with runs of whitespace turned into a dash, so
+/// " Vacation 2026" and "vacation-2026" are the same tag.
+pub fn normalize(name: &str) -> Result<String, String> {
+    let name = name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    if name.is_empty() {
+        return Err("Tag name is required".into());
+    }
+    if name.chars().count() > MAX_LEN {
+        return Err(format!("Tag '{name}' is longer than {MAX_LEN} characters"));
+    }
+    Ok(name)
+}
+
+/// Condition on `transactions` rows for a tag filter over normalised names.
+pub fn condition(mode: Match, names: &[String]) -> SimpleExpr {
+    let placeholders = (1..=names.len())
+        .map(|i| format!("${i}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let values: Vec<Value> = names.iter().map(|n| n.as_str().into()).collect();
+    let tagged = format!(
+        "FROM transaction_tags tt JOIN tags t ON t.id = tt.tag_id \
+         WHERE tt.transaction_id = transactions.id AND t.name IN ({placeholders})"
+    );
+
+    let sql = match mode {
+        Match::All => format!("(SELECT COUNT(DISTINCT t.id) {tagged}) = {}", names.len()),
+        Match::Any => format!("EXISTS (SELECT 1 {tagged})"),
+        Match::None => format!("NOT EXISTS (SELECT 1 {tagged})"),
+    };
+    Expr::cust_with_values(sql, values)
+}
+
+/// Look up tags by normalised name, creating the missing ones.
+pub async fn ensure(
+    db: &impl ConnectionTrait,
+    names: &[String],
+) -> Result<Vec<tags::Model>, String> {
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    tags::Entity::insert_many(names.iter().map(|name| tags::ActiveModel {
+        name: Set(name.clone()),
+        ..Default::default()
+    }))
+    .on_conflict(
+        OnConflict::column(tags::Column::Name)
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await
+    .map_err(|e| format!("Tag save error: {}", e))?;
+
+    find(db, names).await
+}
+
+pub async fn find(db: &impl ConnectionTrait, names: &[String]) -> Result<Vec<tags::Model>, String> {
+    tags::Entity::find()
+        .filter(tags::Column::Name.is_in(names.iter().cloned()))
+        .all(db)
+        .await
+        .map_err(|e| format!("Tag lookup error: {}", e))
+}
+
+/// Current tag names per transaction.
+pub async fn names_for(
+    db: &impl ConnectionTrait,
+    transaction_ids: &[i32],
+) -> Result<HashMap<i32, Vec<String>>, String> {
+    let mut names: HashMap<i32, Vec<String>> = HashMap::new();
+    for chunk in transaction_ids.chunks(CHUNK) {
+        let rows: Vec<(i32, String)> = transaction_tags::Entity::find()
+            .select_only()
+            .column(transaction_tags::Column::TransactionId)
+            .column(tags::Column::Name)
+            .join(JoinType::InnerJoin, transaction_tags::Relation::Tag.def())
+            .filter(transaction_tags::Column::TransactionId.is_in(chunk.iter().copied()))
+            .order_by_asc(tags::Column::Name)
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| format!("Tag lookup error: {}", e))?;
+        for (transaction_id, name) in rows {
+            names.entry(transaction_id).or_default().push(name);
+        }
+    }
+    Ok(names)
+}
+
+/// Tag every transaction with every tag; existing links are kept.
+/// Returns the number of links created.
+pub async fn add(
+    db: &impl ConnectionTrait,
+    transaction_ids: &[i32],
+    tag_ids: &[i32],
+) -> Result<u64, String> {
+    if tag_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let mut added = 0;
+    for chunk in transaction_ids.chunks((CHUNK / tag_ids.len()).max(1)) {
+        let links = chunk.iter().flat_map(|&transaction_id| {
+            tag_ids
+                .iter()
+                .map(move |&tag_id| transaction_tags::ActiveModel {
+                    transaction_id: Set(transaction_id),
+                    tag_id: Set(tag_id),
+                })
+        });
+
+        added += transaction_tags::Entity::insert_many(links)
+            .on_conflict(
+                OnConflict::columns([
+                    transaction_tags::Column::TransactionId,
+                    transaction_tags::Column::TagId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await
+            .map_err(|e| format!("Tag link error: {}", e))?;
+    }
+    Ok(added)
+}
+
+/// Returns the number of links removed.
+pub async fn remove(
+    db: &impl ConnectionTrait,
+    transaction_ids: &[i32],
+    tag_ids: &[i32],
+) -> Result<u64, String> {
+    if tag_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for chunk in transaction_ids.chunks(CHUNK) {
+        removed += transaction_tags::Entity::delete_many()
+            .filter(transaction_tags::Column::TransactionId.is_in(chunk.iter().copied()))
+            .filter(transaction_tags::Column::TagId.is_in(tag_ids.iter().copied()))
+            .exec(db)
+            .await
+            .map_err(|e| format!("Tag unlink error: {}", e))?
+            .rows_affected;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::{PostgresQueryBuilder, Query};
+
+    use crate::services::filter::TransactionFilter;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(" Vacation  2026 ").unwrap(), "vacation-2026");
+        assert_eq!(normalize("reimbursable").unwrap(), "reimbursable");
+        assert!(normalize("  ").is_err());
+        assert!(normalize(&"x".repeat(MAX_LEN + 1)).is_err());
+        assert_eq!("none".parse::<Match>(), Ok(Match::None));
+        assert!("some".parse::<Match>().is_err());
+    }
+
+    #[test]
+    fn test_condition() {
+        let names = vec!["kids".to_string(), "vacation-2026".to_string()];
+        let sql = |mode| {
+            Query::select()
+                .expr(Expr::val(1))
+                .and_where(condition(mode, &names))
+                .to_string(PostgresQueryBuilder)
+        };
+
+        let all = sql(Match::All);
+        assert!(all.contains("COUNT(DISTINCT t.id)"), "{all}");
+        assert!(all.contains("IN ('kids', 'vacation-2026')) = 2"), "{all}");
+        assert!(sql(Match::Any).contains("WHERE EXISTS (SELECT 1"));
+        assert!(sql(Match::None).contains("WHERE NOT EXISTS (SELECT 1"));
+
+        // The same tag named twice is one tag
+        let filter = TransactionFilter {
+            tags: Some("kids,Kids".into()),
+            ..Default::default()
+        };
+        let repeated = Query::select()
+            .expr(Expr::val(1))
+            .cond_where(filter.condition().unwrap())
+            .to_string(PostgresQueryBuilder);
+        assert!(repeated.contains("IN ('kids')) = 1"), "{repeated}");
+    }
+}
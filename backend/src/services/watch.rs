@@ -0,0 +1,236 @@
+use sea_orm::DatabaseConnection;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{error, info, warn};
+
+use crate::services::import::{self, FileImport};
+
+const DONE_DIR: &str = "done";
+const FAILED_DIR: &str = "failed";
+
+/// Suffixes browsers and sync clients use while a download is still in flight.
+const PARTIAL_SUFFIXES: [&str; 5] = [".part", ".partial", ".crdownload", ".download", ".tmp"];
+
+/// Size and mtime seen on the previous scan; a file is only picked up once both
+/// stay the same across two scans, so half-synced downloads are left alone.
+type Snapshot = (u64, Option<SystemTime>);
+
+/// Watch `inbox` for new statement files and import them.
+///
+/// Every `interval` the directory is scanned. Stable files are imported through
+/// [`import::import_batch`] (so ZIP archives work too) and then moved to
+/// `done/`, or to `failed/` when nothing in them could be imported. Files
+/// imported before count as imported. Errors are listed per entry in a
+/// `<name>.error.txt` sidecar next to the moved file, in either directory.
+pub async fn watch(
+    db: DatabaseConnection,
+    archive_dir: PathBuf,
+    inbox: PathBuf,
+    interval: Duration,
+) -> Result<(), String> {
+    for sub in [DONE_DIR, FAILED_DIR] {
+        let dir = inbox.join(sub);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    info!("Watching {} for new statements", inbox.display());
+
+    let mut seen: HashMap<PathBuf, Snapshot> = HashMap::new();
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        let current = match scan(&inbox).await {
+            Ok(current) => current,
+            Err(e) => {
+                error!("Inbox scan failed: {}", e);
+                continue;
+            }
+        };
+
+        for (path, snapshot) in &current {
+            if seen.get(path) == Some(snapshot) {
+                process(&db, &archive_dir, &inbox, path).await;
+            }
+        }
+
+        seen = current;
+    }
+}
+
+async fn scan(inbox: &Path) -> Result<HashMap<PathBuf, Snapshot>, String> {
+    let mut entries = tokio::fs::read_dir(inbox)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", inbox.display(), e))?;
+
+    let mut files = HashMap::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", inbox.display(), e))?
+    {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !is_candidate(&name) {
+            continue;
+        }
+
+        let Ok(meta) = entry.metadata().await else {
+            continue;
+        };
+        if meta.is_file() {
+            files.insert(entry.path(), (meta.len(), meta.modified().ok()));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Hidden files and in-progress downloads are never imported.
+fn is_candidate(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    !name.starts_with('.') && !PARTIAL_SUFFIXES.iter().any(|s| lower.ends_with(s))
+}
+
+async fn process(db: &DatabaseConnection, archive_dir: &Path, inbox: &Path, path: &Path) {
+    let filename = path
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unknown".into());
+
+    let (imported, mut errors) = match tokio::fs::read(path).await {
+        Ok(bytes) => {
+            summarize(import::import_batch(db, archive_dir, vec![(filename.clone(), bytes)]).await)
+        }
+        Err(e) => (false, vec![format!("{}: failed to read: {}", filename, e)]),
+    };
+    if !imported && errors.is_empty() {
+        errors.push(format!("{}: no statement files found", filename));
+    }
+
+    let dir = if imported {
+        if errors.is_empty() {
+            info!("Imported {} from inbox", filename);
+        } else {
+            warn!(
+                "Imported {} from inbox, {} entries failed",
+                filename,
+                errors.len()
+            );
+        }
+        DONE_DIR
+    } else {
+        warn!("Import of {} from inbox failed", filename);
+        FAILED_DIR
+    };
+
+    let result = match move_to(inbox, dir, path, &filename).await {
+        Ok(dest) if !errors.is_empty() => {
+            let mut sidecar = dest.into_os_string();
+            sidecar.push(".error.txt");
+            tokio::fs::write(&sidecar, errors.join("\n") + "\n")
+                .await
+                .map_err(|e| format!("Failed to write error sidecar: {e}"))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        error!("Failed to file away {}: {}", filename, e);
+    }
+}
+
+/// Whether anything in a dropped file is in the database now (a duplicate
+/// file was imported before), and the errors of the entries that failed.
+fn summarize(results: Vec<FileImport>) -> (bool, Vec<String>) {
+    let imported = results.iter().any(|f| f.result.is_ok() || f.duplicate);
+    let errors = results
+        .into_iter()
+        .filter(|f| !f.duplicate)
+        .filter_map(|f| f.result.err().map(|e| format!("{}: {}", f.filename, e)))
+        .collect();
+    (imported, errors)
+}
+
+async fn move_to(inbox: &Path, sub: &str, path: &Path, filename: &str) -> Result<PathBuf, String> {
+    let dir = inbox.join(sub);
+    let mut dest = dir.join(filename);
+    let mut n = 1;
+    while tokio::fs::try_exists(&dest).await.unwrap_or(false) {
+        dest = dir.join(numbered(filename, n));
+        n += 1;
+    }
+
+    tokio::fs::rename(path, &dest)
+        .await
+        .map_err(|e| format!("Failed to move to {}: {}", dest.display(), e))?;
+
+    Ok(dest)
+}
+
+/// "statement.csv" → "statement (2).csv", so a re-dropped file never
+/// overwrites the copy already filed away.
+fn numbered(filename: &str, n: u32) -> String {
+    match filename.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{stem} ({}).{ext}", n + 1),
+        _ => format!("{filename} ({})", n + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_candidate_skips_hidden_and_partial() {
+        assert!(is_candidate("Lista_operacji_20260301.csv"));
+        assert!(is_candidate("statements.zip"));
+        assert!(!is_candidate(".DS_Store"));
+        assert!(!is_candidate("account-statement.csv.crdownload"));
+        assert!(!is_candidate("Historia_Operacji.csv.PART"));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let ok = |name: &str| FileImport {
+            filename: name.into(),
+            result: Ok(import::ImportResult {
+                total_rows: 1,
+                imported: 1,
+                skipped: 0,
+            }),
+            duplicate: false,
+        };
+        let failed = |name: &str, duplicate| FileImport {
+            filename: name.into(),
+            result: Err("bad".into()),
+            duplicate,
+        };
+
+        // A re-dropped file is done, not failed
+        assert_eq!(summarize(vec![failed("a.csv", true)]), (true, vec![]));
+
+        // A ZIP with one bad entry is done, with that entry's error
+        assert_eq!(
+            summarize(vec![ok("s.zip/a.csv"), failed("s.zip/b.csv", false)]),
+            (true, vec!["s.zip/b.csv: bad".to_string()])
+        );
+
+        assert_eq!(
+            summarize(vec![failed("a.csv", false)]),
+            (false, vec!["a.csv: bad".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_numbered() {
+        assert_eq!(numbered("statement.csv", 1), "statement (2).csv");
+        assert_eq!(numbered("statement.csv", 2), "statement (3).csv");
+        assert_eq!(numbered("README", 1), "README (2)");
+        assert_eq!(numbered(".env", 1), ".env (2)");
+    }
+}
@@ -6,6 +6,7 @@ import type {
   MonthlyStat,
   CategoryStat,
   ImportResult,
+  Job,
 } from "./types";
 
 const API_BASE = process.env.NEXT_PUBLIC_API_URL || "http://localhost:3001";
@@ -96,8 +97,19 @@ export async function importFile(file: File): Promise<ImportResult> {
   return res.json();
 }
 
-export async function triggerCategorize(): Promise<{ categorized: number }> {
-  return apiFetch<{ categorized: number }>("/api/categorize", {
+/** Starts (or returns the already running) background categorization job. */
+export async function triggerCategorize(): Promise<Job> {
+  return apiFetch<Job>("/api/categorize", {
+    method: "POST",
+  });
+}
+
+export async function getJob(id: number): Promise<Job> {
+  return apiFetch<Job>(`/api/jobs/${id}`);
+}
+
+export async function cancelJob(id: number): Promise<Job> {
+  return apiFetch<Job>(`/api/jobs/${id}/cancel`, {
     method: "POST",
   });
 }
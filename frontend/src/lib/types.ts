@@ -16,6 +16,53 @@ export interface Transaction {
   state: string | null;
   raw_data: Record<string, unknown> | null;
   imported_at: string | null;
+  notes: string | null;
+  reviewed_at: string | null;
+  review_status: "new" | "reviewed" | "flagged";
+  source: "import" | "manual";
+  deleted_at: string | null;
+}
+
+export interface Attachment {
+  id: number;
+  transaction_id: number;
+  filename: string;
+  content_type: string;
+  size: number;
+  sha256: string;
+  created_at: string;
+}
+
+export interface TransactionSplit {
+  id: number;
+  transaction_id: number;
+  category_id: number | null;
+  amount: string;
+  note: string | null;
+}
+
+export interface TransactionHistoryEntry {
+  id: number;
+  transaction_id: number;
+  field: string;
+  old_value: unknown;
+  new_value: unknown;
+  changed_by: string;
+  reason: string;
+  changed_at: string;
+}
+
+export interface Tag {
+  id: number;
+  name: string;
+  count: number;
+}
+
+export interface TagStat {
+  tag: string;
+  income: string;
+  expense: string;
+  count: number;
 }
 
 export interface TransactionListResponse {
@@ -29,6 +76,8 @@ export interface Category {
   id: number;
   name: string;
   name_pl: string | null;
+  parent_id: number | null;
+  archived_at: string | null;
 }
 
 export interface Account {
@@ -36,6 +85,7 @@ export interface Account {
   name: string;
   currency: string;
   transaction_count: number;
+  unreviewed_count: number;
 }
 
 export interface MonthlyStat {
@@ -45,7 +95,10 @@ export interface MonthlyStat {
 }
 
 export interface CategoryStat {
+  category_id: number | null;
   category: string | null;
+  parent_id: number | null;
+  depth: number | null;
   total: string;
   count: number;
 }
@@ -55,3 +108,22 @@ export interface ImportResult {
   imported: number;
   skipped: number;
 }
+
+export interface Job {
+  id: number;
+  kind: string;
+  status: "queued" | "running" | "completed" | "failed" | "cancelled";
+  total: number;
+  processed: number;
+  succeeded: number;
+  failed: number;
+  cached: number;
+  prompt_tokens: number;
+  completion_tokens: number;
+  estimated_cost: string;
+  error: string | null;
+  cancel_requested: boolean;
+  created_at: string;
+  started_at: string | null;
+  finished_at: string | null;
+}